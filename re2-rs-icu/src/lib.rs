@@ -1 +1,105 @@
+//! RE2 with ICU's Unicode properties. Same API as `re2-rs`, except that [`Options`]
+//! and [`Regex`] here default to [`Engine::Icu`], so `Regex::new(r"\p{Emoji}")` works.
+//! The default is a property of this crate, not of the build: `re2_rs::Regex` keeps
+//! [`Engine::Plain`] in the same binary.
+//!
+//! [`Regex::new`] and [`Regex::with_options`] return [`Error`], not `String` as in
+//! earlier versions: RE2's message is in [`Error::Compile`], and [`Error`]'s `Display`
+//! prints it with a "RE2 compile error: " prefix.
+
+use std::ops::Deref;
+
 pub use re2_rs_wrapper::*;
+
+/// [`re2_rs_wrapper::Options`] defaulting to [`Engine::Icu`]
+///
+/// Dereferences to the wrapper's options, so it can be passed wherever those are taken,
+/// e.g. to [`RegexSet::new`] or [`FilteredRegexSet::add`].
+#[derive(Clone)]
+pub struct Options(re2_rs_wrapper::Options);
+
+macro_rules! forward_options {
+    ($($name:ident($ty:ty)),* $(,)?) => {
+        $(
+            #[doc = concat!("See [`re2_rs_wrapper::Options::", stringify!($name), "`]")]
+            pub fn $name(self, value: $ty) -> Self {
+                Options(self.0.$name(value))
+            }
+        )*
+    };
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Options(re2_rs_wrapper::Options::new().engine(Engine::Icu))
+    }
+
+    forward_options! {
+        case_insensitive(bool),
+        posix_syntax(bool),
+        longest_match(bool),
+        unicode_word_boundaries(bool),
+        perl_classes(bool),
+        unicode_perl_classes(bool),
+        log_errors(bool),
+        max_mem(i64),
+        engine(Engine),
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for Options {
+    type Target = re2_rs_wrapper::Options;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Options> for re2_rs_wrapper::Options {
+    fn from(opts: Options) -> Self {
+        opts.0
+    }
+}
+
+/// [`re2_rs_wrapper::Regex`] whose constructors default to [`Engine::Icu`]
+///
+/// Dereferences to the wrapper's regex for matching and for [`debug`].
+pub struct Regex(re2_rs_wrapper::Regex);
+
+impl Regex {
+    /// Compile `pattern` with the ICU engine and otherwise default options
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        Self::with_options(pattern, &Options::new())
+    }
+
+    /// Compile `pattern` with `opts`. Options built with this crate's [`Options`] use
+    /// the ICU engine unless they select another.
+    pub fn with_options(pattern: &str, opts: &re2_rs_wrapper::Options) -> Result<Self, Error> {
+        re2_rs_wrapper::Regex::with_options(pattern, opts).map(Regex)
+    }
+
+    /// See [`re2_rs_wrapper::Regex::with_policy`]
+    pub fn with_policy(pattern: &str, opts: &re2_rs_wrapper::Options, policy: &CompilePolicy) -> Result<Self, Error> {
+        re2_rs_wrapper::Regex::with_policy(pattern, opts, policy).map(Regex)
+    }
+}
+
+impl Deref for Regex {
+    type Target = re2_rs_wrapper::Regex;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Regex> for re2_rs_wrapper::Regex {
+    fn from(re: Regex) -> Self {
+        re.0
+    }
+}
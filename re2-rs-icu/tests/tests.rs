@@ -11,4 +11,53 @@ mod unicode;
 mod unicode_props;
 
 #[path = "../../tests/src/options.rs"]
-mod options;
\ No newline at end of file
+mod options;
+
+#[path = "../../tests/src/set.rs"]
+mod set;
+
+#[path = "../../tests/src/filtered.rs"]
+mod filtered;
+
+// Built on RE2 internals, which system-re2 compiles out
+#[cfg(not(feature = "system-re2"))]
+#[path = "../../tests/src/prefilter.rs"]
+mod prefilter;
+
+#[cfg(not(feature = "system-re2"))]
+#[path = "../../tests/src/ast.rs"]
+mod ast;
+
+#[cfg(not(feature = "system-re2"))]
+#[path = "../../tests/src/canonical.rs"]
+mod canonical;
+
+#[cfg(not(feature = "system-re2"))]
+#[path = "../../tests/src/pcre.rs"]
+mod pcre;
+
+#[cfg(not(feature = "system-re2"))]
+#[path = "../../tests/src/debug.rs"]
+mod debug;
+
+#[path = "../../tests/src/hooks.rs"]
+mod hooks;
+
+#[path = "../../tests/src/logging.rs"]
+mod logging;
+
+#[path = "../../tests/src/policy.rs"]
+mod policy;
+
+#[path = "../../tests/src/memory.rs"]
+mod memory;
+
+#[path = "../../tests/src/build_info.rs"]
+mod build_info;
+
+#[cfg(feature = "system-re2")]
+#[path = "../../tests/src/system_re2.rs"]
+mod system_re2;
+
+#[path = "../../tests/src/engines.rs"]
+mod engines;
@@ -1,11 +1,14 @@
-use std::{env, fs, path::PathBuf, process::Command};
+use std::{env, fs, io::ErrorKind, path::PathBuf, process::Command};
 use std::path::Path;
 
 //
 // --- Build re2-rs / re2-rs-icu ---
 //
 // 1. ICU
-//    - Linked dynamically if the `icu` feature is enabled.
+//    - Linked dynamically if the `icu` feature is enabled, for the ICU engine (step 6).
+//    - With `icu-static`, the static archives (libicuuc.a, libicui18n.a, libicudata.a) are
+//      linked instead, from ICU_ROOT if set or else `pkg-config --static`, so the binary
+//      does not need libicu at runtime.
 //    - We do not vendor ICU source here: shipping the full tree would bloat the crate (>100 MB).
 //    - On Linux/macOS: expect ICU to be available via system packages (e.g. libicu-dev, icu-devel, or Homebrew icu4c).
 //    - On Windows: expect a prebuilt ICU release to be downloaded/unzipped and exposed via the
@@ -14,16 +17,58 @@ use std::path::Path;
 //
 // 2. Abseil
 //    - Required by RE2.
-//    - Lightweight, can be built directly with `cc` from vendored sources.
+//    - Only the sources listed in absl-sources.txt, which `cargo run -p xtask -- absl-manifest`
+//      generates by following the includes of RE2 and the shim, are built with `cc`.
 //
 // 3. RE2
 //    - Core regular expression engine.
 //    - Also small enough to vendor and build directly.
+//    - dfa.cc is compiled through src/footprint.cc, which measures the DFA caches the
+//      shim reports in re2_approx_heap_bytes and needs the DFA class defined there.
 //
 // 4. re2-rs bindings
 //    - Unsafe C bindings (c-bindings.cc/h) wrapping RE2 for use in Rust.
 //    - Bindings are either generated with `bindgen` or copied from a pregenerated file.
 //
+// 5. C++ runtime
+//    - RE2_RS_CXX_STDLIB=dynamic|static|none picks how it is linked; the library itself is
+//      `cc`'s choice (libstdc++, or libc++ on Apple/BSD) unless CXXSTDLIB names another.
+//    - `dynamic` (the default) leaves it to `cc`. `static` links the archive the C++ compiler
+//      reports, and is the default for musl targets, which have no shared libstdc++ to load.
+//      `none` links nothing, for builds whose final link supplies the runtime itself.
+//
+// 6. ICU engine (`icu` feature only)
+//    - A second copy of RE2 built with ICU, with `re2` defined to `re2_icu` so its symbols
+//      do not clash with the plain copy. It only parses (see src/icu-engine.cc), so both
+//      engines are available in one binary and each pattern picks one via its options.
+//
+// 7. docs-only
+//    - With the `docs-only` feature, or on docs.rs (DOCS_RS set), none of the C++ is built.
+//      The bindings are still generated, and every function c-bindings.h declares also
+//      gets an aborting stub definition (docs_stubs.rs), so documentation builds need no
+//      C++ toolchain. The facades forward the feature.
+//
+// 8. Tuning (all C++ builds)
+//    - `cxx-release`: -O3 and NDEBUG whatever the Cargo profile, so debug builds of the
+//      Rust code can still link a release-tuned RE2.
+//    - `cxx-lto`: -flto=thin objects for cross-language LTO. Needs clang and llvm-ar for the
+//      C++ (CC/CXX/AR), and RUSTFLAGS="-Clinker-plugin-lto -Clinker=clang -Clink-arg=-fuse-ld=lld".
+//    - RE2_RS_SANITIZE=address,undefined: instrument the C++ only. The runtime comes from the
+//      Rust side, e.g. nightly RUSTFLAGS=-Zsanitizer=address, which must use the same one.
+//
+// 9. Prebuilt archives
+//    - RE2_RS_PREBUILT_DIR=<dir> caches the Abseil archives and libre2_core.a across target
+//      directories and checkouts, in <dir>/<hash>/. The hash covers vendor/, the shim, the
+//      Abseil manifest, the target, and the compiler and its flags, so any change to those
+//      builds (and stores) a fresh set instead of reusing a stale one.
+//
+// With the `system-re2` feature steps 2 and 3 are skipped: RE2 and Abseil are found with
+// pkg-config and only c-bindings.cc is compiled, with RE2_RS_SYSTEM_RE2 defined. The
+// shim then sees RE2's public headers only: the internal ones (prog.h, regexp.h,
+// prefilter.h) are not installed, and vendor/re2's would not match another release's
+// layout, so what the shim builds on them is compiled out. The library must be from the
+// vendored release's SONAME series, whose public ABI the bindings were generated against.
+//
 fn main() {
     let vendor = PathBuf::from("../vendor");
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
@@ -36,27 +81,24 @@ fn main() {
     // --- Rebuild triggers ---
     println!("cargo:rerun-if-changed=src/c-bindings.cc");
     println!("cargo:rerun-if-changed=src/c-bindings.h");
+    println!("cargo:rerun-if-changed=src/icu-engine.cc");
+    println!("cargo:rerun-if-changed=src/icu-engine.h");
+    println!("cargo:rerun-if-changed=src/footprint.cc");
+    println!("cargo:rerun-if-changed=src/footprint.h");
     println!("cargo:rerun-if-changed=../vendor/re2");
     println!("cargo:rerun-if-changed=../vendor/abseil-cpp");
-
-    if with_icu {
-        println!("--- Linking ICU (system/prebuilt) ---");
-        link_icu();
-        println!("--- Finished ICU setup ---");
+    println!("cargo:rerun-if-changed={}", ABSL_MANIFEST);
+
+    println!("cargo:rerun-if-env-changed=DOCS_RS");
+    println!("cargo:rustc-check-cfg=cfg(re2_docs_only)");
+    let docs_only = cfg!(feature = "docs-only") || env::var_os("DOCS_RS").is_some();
+    if docs_only {
+        println!("--- docs-only: skipping ICU, Abseil and RE2 ---");
+        emit_build_info(RE2_RELEASE, None);
+    } else {
+        build_native(&vendor, with_icu);
     }
 
-    println!("--- Building Abseil (subset) ---");
-    build_absl(&vendor, with_icu);
-    println!("--- Finished Abseil ---");
-
-    println!("--- Building RE2 ---");
-    build_re2(vendor, with_icu);
-    println!("--- Finished RE2 ---");
-
-    println!("cargo:rustc-link-lib=static=re2_core");
-    println!("cargo:rerun-if-changed=src/c-bindings.h");
-    println!("cargo:rerun-if-changed=src/c-bindings.cc");
-
     //
     // --- Bindings mode (dual) ---
     //
@@ -66,6 +108,7 @@ fn main() {
         let bindings = bindgen::Builder::default()
             .header("src/c-bindings.h")
             .allowlist_function("re2_.*")
+            .allowlist_var("RE2_.*")
             .allowlist_type("RE2Wrapper")
             .generate()
             .expect("Unable to generate bindings");
@@ -85,9 +128,188 @@ fn main() {
         fs::copy(&src, &dst).expect("Couldn't copy pregenerated bindings");
     }
 
+    if docs_only {
+        println!("cargo:rustc-cfg=re2_docs_only");
+        write_docs_stubs(Path::new("src/c-bindings.h"), &out_dir.join("docs_stubs.rs"));
+    }
+
     println!("=== build.rs end ===");
 }
 
+fn build_native(vendor: &Path, with_icu: bool) {
+    let icu = with_icu.then(|| {
+        println!("--- Probing ICU (system/prebuilt) ---");
+        let cfg = find_icu();
+        println!("--- Finished ICU setup ---");
+        cfg
+    });
+
+    let system = cfg!(feature = "system-re2").then(|| {
+        println!("--- Probing RE2 + Abseil (system, pkg-config) ---");
+        probe_system_re2()
+    });
+
+    let absl_includes = if let Some(libs) = &system {
+        println!("--- Building shim (system RE2) ---");
+        let includes = include_paths(libs);
+        build_shim(&includes, with_icu);
+        println!("--- Finished shim ---");
+        includes
+    } else {
+        let prebuilt = prebuilt_dir(vendor, with_icu);
+        match &prebuilt {
+            Some(dir) if link_prebuilt(dir) => {
+                println!("--- Using prebuilt RE2 + Abseil from {} ---", dir.display());
+            }
+            _ => {
+                println!("--- Building Abseil (subset) ---");
+                let mut libs = build_absl(vendor);
+                println!("--- Finished Abseil ---");
+
+                println!("--- Building RE2 ---");
+                build_re2(vendor, with_icu);
+                println!("cargo:rustc-link-lib=static=re2_core");
+                println!("--- Finished RE2 ---");
+                libs.push("re2_core".into());
+
+                if let Some(dir) = &prebuilt {
+                    store_prebuilt(dir, &libs);
+                }
+            }
+        }
+        vec![vendor.join("abseil-cpp")]
+    };
+
+    if let Some(icu) = &icu {
+        println!("--- Building RE2 (ICU engine) ---");
+        build_re2_icu(vendor, &absl_includes, icu);
+        println!("--- Finished RE2 (ICU engine) ---");
+    }
+
+    // After the static archives that depend on them, for single-pass linkers
+    if let Some(libs) = &system {
+        link_system_re2(libs);
+    }
+    if let Some(icu) = &icu {
+        println!("--- Linking ICU ---");
+        link_icu(icu);
+    }
+    link_cxx_stdlib();
+
+    let re2_version = match &system {
+        Some(libs) => format!("system {}", libs[0].version),
+        None => RE2_RELEASE.to_string(),
+    };
+    let mut reference = cc::Build::new();
+    let is_msvc = reference.get_compiler().is_like_msvc();
+    add_common_defines(&mut reference, is_msvc);
+    emit_build_info(&re2_version, Some(&reference.get_compiler()));
+}
+
+/// Facts for `re2_rs::build_info()`, read back with env! in src/lib.rs
+fn emit_build_info(re2_version: &str, compiler: Option<&cc::Tool>) {
+    println!("cargo:rustc-env=RE2_RS_RE2_VERSION={}", re2_version);
+    let (path, flags) = match compiler {
+        Some(tool) => (
+            tool.path().display().to_string(),
+            tool.args().iter().map(|a| a.to_string_lossy()).collect::<Vec<_>>().join(" "),
+        ),
+        None => (String::new(), String::new()),
+    };
+    println!("cargo:rustc-env=RE2_RS_CXX_COMPILER={}", path);
+    println!("cargo:rustc-env=RE2_RS_CXX_FLAGS={}", flags);
+}
+
+/// Define every function declared in c-bindings.h as an exported stub that aborts, so
+/// docs-only builds link without the C++ code. Read from the header itself rather than
+/// bindings.rs, whose pregenerated copy can lag behind it.
+fn write_docs_stubs(header: &Path, dest: &Path) {
+    let text = fs::read_to_string(header).expect("Couldn't read c-bindings.h");
+    let code: String = text
+        .lines()
+        .map(|line| line.split("//").next().unwrap())
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut out = String::from("// Generated by build.rs from c-bindings.h in docs-only mode\n");
+    let mut body = code.as_str();
+    // The declarations sit inside `extern "C" { ... }`; struct bodies have no `(`
+    while let Some(at) = body.find(';') {
+        let decl = body[..at].trim().trim_start_matches("extern \"C\" {").trim();
+        body = &body[at + 1..];
+        if decl.starts_with("typedef") || !decl.contains('(') {
+            continue;
+        }
+        let open = decl.find('(').unwrap();
+        let close = decl.rfind(')').expect("unterminated parameter list in c-bindings.h");
+        let (ret, name) = c_declarator(&decl[..open]);
+        let ret = ret.expect("function without a return type in c-bindings.h");
+        let params = decl[open + 1..close].trim();
+        let params: Vec<String> = if params.is_empty() || params == "void" {
+            Vec::new()
+        } else {
+            params
+                .split(',')
+                .enumerate()
+                .map(|(i, param)| match c_declarator(param) {
+                    (Some(ty), name) => format!("{}: {}", name, rust_type(&ty)),
+                    (None, ty) => format!("arg{}: {}", i, rust_type(&ty)),
+                })
+                .collect()
+        };
+        let ret = if ret == "void" { String::new() } else { format!(" -> {}", rust_type(&ret)) };
+        out.push_str(&format!(
+            "#[unsafe(no_mangle)]\npub extern \"C\" fn {}({}){} {{\n    docs_only_stub(\"{}\")\n}}\n",
+            name,
+            params.join(", "),
+            ret,
+            name
+        ));
+    }
+    fs::write(dest, out).expect("Couldn't write docs_stubs.rs");
+}
+
+/// Split a C declaration such as `const char** err_ptr` into its type and name. A lone
+/// type (an unnamed parameter) comes back as `(None, type)`.
+fn c_declarator(decl: &str) -> (Option<String>, String) {
+    let decl = decl.trim();
+    let split = decl.rfind(|c: char| c == '*' || c.is_whitespace());
+    match split {
+        Some(at) if at + 1 < decl.len() && !decl[..=at].trim().is_empty() => {
+            let ty = decl[..=at].split_whitespace().collect::<Vec<_>>().join(" ");
+            (Some(ty), decl[at + 1..].to_string())
+        }
+        _ => (None, decl.to_string()),
+    }
+}
+
+/// Rust spelling of a C type from c-bindings.h. Only a leading `const` occurs there, and
+/// it qualifies the innermost pointee.
+fn rust_type(c: &str) -> String {
+    let c = c.replace(' ', "");
+    let (is_const, c) = match c.strip_prefix("const") {
+        Some(rest) => (true, rest),
+        None => (false, c.as_str()),
+    };
+    let base = c.trim_end_matches('*');
+    let mut ty = match base {
+        "char" => "::std::os::raw::c_char".to_string(),
+        "int" => "::std::os::raw::c_int".to_string(),
+        "size_t" => "usize".to_string(),
+        "int32_t" => "i32".to_string(),
+        "int64_t" => "i64".to_string(),
+        "void" => "::std::os::raw::c_void".to_string(),
+        // The header's own structs and callback typedefs, as bindings.rs names them
+        _ => base.to_string(),
+    };
+    for i in 0..c.len() - base.len() {
+        let qualifier = if i == 0 && is_const { "*const" } else { "*mut" };
+        ty = format!("{} {}", qualifier, ty);
+    }
+    ty
+}
+
 struct IcuConfig {
     include_paths: Vec<PathBuf>,
     link_paths: Vec<PathBuf>,
@@ -147,15 +369,50 @@ fn probe_icu() -> Option<IcuConfig> {
     None
 }
 
-fn link_icu() {
+/// Probe static ICU archives: ICU_ROOT → `pkg-config --static`
+fn probe_icu_static() -> Option<IcuConfig> {
+    if let Ok(icu_root) = env::var("ICU_ROOT") {
+        let root = PathBuf::from(&icu_root);
+        return Some(IcuConfig {
+            include_paths: vec![root.join("include")],
+            link_paths: vec![root.join("lib64"), root.join("lib")],
+            libs: Vec::new(),
+        });
+    }
 
-    let mut cfg = probe_icu().unwrap_or_else(|| {
+    let lib = pkg_config::Config::new()
+        .statik(true)
+        .cargo_metadata(false)
+        .probe("icu-i18n")
+        .ok()?;
+    let mut link_paths = lib.link_paths;
+    // pkg-config leaves out -L for system directories, but rustc needs the directory to
+    // find (and bundle) a static archive
+    if let Ok(libdir) = pkg_config::get_variable("icu-i18n", "libdir") {
+        link_paths.push(PathBuf::from(libdir));
+    }
+    Some(IcuConfig {
+        include_paths: lib.include_paths,
+        link_paths,
+        libs: lib.libs,
+    })
+}
+
+fn find_icu() -> IcuConfig {
+    let static_icu = cfg!(feature = "icu-static");
+    let probed = if static_icu { probe_icu_static() } else { probe_icu() };
+    let mut cfg = probed.unwrap_or_else(|| {
         println!("cargo:warning=ICU not found. Set ICU_ROOT (Windows) or install via pkg-config/Homebrew.");
         panic!("ICU not found; cannot build with feature `icu`");
     });
 
     // Attempt to Guarantee essential libs for RE2+ICU - dif names across platforms
-    ensure_icu_libs(&mut cfg);
+    ensure_icu_libs(&mut cfg, static_icu);
+    cfg
+}
+
+fn link_icu(cfg: &IcuConfig) {
+    let static_icu = cfg!(feature = "icu-static");
 
     for path in &cfg.include_paths {
         println!("cargo:include={}", path.display());
@@ -164,11 +421,22 @@ fn link_icu() {
         println!("cargo:rustc-link-search=native={}", lib_path.display());
     }
     for lib in &cfg.libs {
-        println!("cargo:rustc-link-lib={}", lib);
+        if static_icu && is_icu_lib(lib) {
+            if !has_static_archive(cfg, lib) {
+                panic!(
+                    "static ICU archive for `{}` not found in {:?}; install the static ICU libraries \
+                     (e.g. libicu-dev) or point ICU_ROOT at a static ICU build",
+                    lib, cfg.link_paths
+                );
+            }
+            println!("cargo:rustc-link-lib=static={}", lib);
+        } else {
+            println!("cargo:rustc-link-lib={}", lib);
+        }
     }
 
     // Windows-only: copy DLLs into target dir for test runs
-    if cfg!(target_os = "windows") {
+    if cfg!(target_os = "windows") && !static_icu {
         copy_icu_dlls_for_local_test();
     }
 }
@@ -191,144 +459,280 @@ fn copy_icu_dlls_for_local_test() {
     }
 }
 
-fn build_absl(vendor: &Path, with_icu: bool) {
+/// Abseil sources RE2 and the shim need, regenerated with `cargo run -p xtask -- absl-manifest`
+const ABSL_MANIFEST: &str = "absl-sources.txt";
+
+/// Build the Abseil subset, returning its archives in link order
+fn build_absl(vendor: &Path) -> Vec<String> {
     println!("--- Building Abseil ---");
 
-    let absl_targets: &[(&str, &[&str])] = &[
-        ("absl_base", &[
-            "absl/base/*.cc",
-            "absl/base/internal/*.cc",
-        ]),
-        ("absl_strings", &[
-            "absl/strings/*.cc",
-            "absl/strings/internal/*.cc",
-        ]),
-        ("absl_synchronization", &[
-            "absl/synchronization/*.cc",
-            "absl/synchronization/internal/*.cc"
-        ]),
-        ("absl_time", &[
-            "absl/time/*.cc",
-            "absl/time/internal/*.cc",
-        ]),
-        ("absl_time_cctz", &[
-            "absl/time/internal/cctz/src/*.cc",
-        ]),
-        ("absl_numeric", &["absl/numeric/*.cc"]),
-        ("absl_hash", &[
-            "absl/hash/*.cc",
-            "absl/hash/internal/*.cc",
-        ]),
-        ("absl_log", &[
-            "absl/log/*.cc",
-            "absl/log/internal/*.cc",
-            "absl/log/internal/check_op.cc",
-        ]),
-        ("absl_symbolize", &[
-            "absl/debugging/*.cc",
-            "absl/debugging/internal/*.cc",
-        ]),
-        ("absl_stacktrace", &[
-            "absl/debugging/stacktrace.cc",
-        ]),
-        ("absl_str_format", &[
-            "absl/strings/str_format.cc",
-            "absl/strings/internal/str_format/*.cc",
-        ]),
-        ("absl_container", &[
-            "absl/container/*.cc",
-            "absl/container/internal/*.cc",
-        ]),
-    ];
-
-
-
-    for (libname, patterns) in absl_targets {
+    let manifest = fs::read_to_string(ABSL_MANIFEST).expect("Couldn't read the Abseil manifest");
+
+    // One archive per top-level directory (absl_base, absl_strings, ...), in manifest order
+    let mut libs: Vec<(String, Vec<&str>)> = Vec::new();
+    for source in manifest.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        let dir = source.split('/').nth(1).expect("Abseil manifest entries are absl/<dir>/...");
+        let libname = format!("absl_{}", dir);
+        match libs.last_mut() {
+            Some((name, files)) if *name == libname => files.push(source),
+            _ => libs.push((libname, vec![source])),
+        }
+    }
+
+    for (libname, sources) in &libs {
         let mut absl = cc::Build::new();
         absl.cpp(true).include(vendor.join("abseil-cpp"));
 
         let compiler = absl.get_compiler();
         let is_msvc = compiler.is_like_msvc();
 
-        add_common_defines(&mut absl, is_msvc, with_icu);
-
-        let mut count = 0;
-        for pat in *patterns {
-            let globpat = vendor.join("abseil-cpp").join(pat).display().to_string();
-            for entry in glob::glob(&globpat).unwrap() {
-                let file = entry.unwrap();
-                let path = file.display().to_string();
-                if path.contains("test") || path.contains("benchmark") || path.contains("mock") {
-                    println!("SKIP ABSL {}", path);
-                    continue;
-                }
-                println!("ABSL [{}] {}", libname, path);
-                absl.file(&file);
-                count += 1;
-            }
-        }
+        add_common_defines(&mut absl, is_msvc);
 
-        if count > 0 {
-            absl.compile(libname);
-            println!("Built Abseil library: {} ({} files)", libname, count);
-            println!("cargo:rustc-link-lib=static={}", libname);
-        } else {
-            println!("No source files for {}, skipping", libname);
+        for source in sources {
+            let file = vendor.join("abseil-cpp").join(source);
+            println!("ABSL [{}] {}", libname, file.display());
+            absl.file(&file);
         }
+
+        absl.compile(libname);
+        println!("Built Abseil library: {} ({} files)", libname, sources.len());
+        println!("cargo:rustc-link-lib=static={}", libname);
     }
+    libs.into_iter().map(|(libname, _)| libname).collect()
 }
 
-fn build_re2(vendor: PathBuf, with_icu: bool) {
+fn build_re2(vendor: &Path, with_icu: bool) {
     let mut re2 = cc::Build::new();
 
     re2.include(vendor.join("re2"));
     re2.include(vendor.join("abseil-cpp"));
 
+    let compiler = re2.get_compiler();
+    let is_msvc = compiler.is_like_msvc();
+    add_common_defines(&mut re2, is_msvc);
     if with_icu {
-        if let Some(cfg) = probe_icu() {
-            for inc in &cfg.include_paths {
-                re2.include(inc);
-                println!("cargo:warning=Using ICU include path {}", inc.display());
+        // Lets the shim hand patterns for the ICU engine to it
+        re2.define("RE2_RS_ICU_ENGINE", None);
+    }
+
+    // footprint.cc compiles dfa.cc itself, to reach the DFA class defined there
+    add_re2_sources(&mut re2, "RE2", &["dfa.cc"]);
+    re2.file("src/footprint.cc");
+    re2.file("src/c-bindings.cc");
+
+    re2.compile("re2_core");
+}
+
+/// Release of vendor/re2, which has no version of its own in the tree
+const RE2_RELEASE: &str = "2025-08-12";
+
+/// SONAME of the vendored RE2 release, which re2.pc reports as its major version
+const RE2_SONAME: u64 = 11;
+
+/// Abseil libraries the shim calls into directly; RE2's own are pulled in by re2.pc
+const SHIM_ABSL_LIBS: &[&str] = &[
+    "absl_log_initialize",
+    "absl_log_globals",
+    "absl_log_internal_globals",
+    "absl_log_sink_registry",
+    "absl_str_format",
+    "absl_strings",
+];
+
+/// Probe RE2 and the Abseil libraries the shim uses with pkg-config, checking the RE2 version
+fn probe_system_re2() -> Vec<pkg_config::Library> {
+    let re2 = pkg_config::Config::new()
+        .cargo_metadata(false)
+        .probe("re2")
+        .unwrap_or_else(|e| {
+            println!("cargo:warning=RE2 not found via pkg-config. Install it (e.g. libre2-dev) or drop the `system-re2` feature.");
+            panic!("RE2 not found; cannot build with feature `system-re2`: {}", e);
+        });
+
+    let major = re2.version.split('.').next().and_then(|v| v.parse::<u64>().ok());
+    if major != Some(RE2_SONAME) {
+        panic!(
+            "system RE2 {} is not ABI compatible: the bindings follow RE2 {}, whose \
+             library has SONAME {} (re2.pc version {}.x)",
+            re2.version, RE2_RELEASE, RE2_SONAME, RE2_SONAME
+        );
+    }
+    println!("System RE2 {} (SONAME {})", re2.version, RE2_SONAME);
+
+    let mut libs = vec![re2];
+    for name in SHIM_ABSL_LIBS {
+        let lib = pkg_config::Config::new()
+            .cargo_metadata(false)
+            .probe(name)
+            .unwrap_or_else(|e| panic!("Abseil library {} not found via pkg-config: {}", name, e));
+        libs.push(lib);
+    }
+    libs
+}
+
+fn include_paths(libs: &[pkg_config::Library]) -> Vec<PathBuf> {
+    let mut includes: Vec<PathBuf> = Vec::new();
+    for inc in libs.iter().flat_map(|lib| &lib.include_paths) {
+        if !includes.contains(inc) {
+            includes.push(inc.clone());
+        }
+    }
+    includes
+}
+
+fn link_system_re2(libs: &[pkg_config::Library]) {
+    let mut emitted: Vec<&str> = Vec::new();
+    for lib in libs {
+        for path in &lib.link_paths {
+            println!("cargo:rustc-link-search=native={}", path.display());
+        }
+        for name in &lib.libs {
+            if !emitted.contains(&name.as_str()) {
+                println!("cargo:rustc-link-lib={}", name);
+                emitted.push(name);
             }
         }
     }
+}
+
+/// Compile only the shim against a system RE2
+fn build_shim(includes: &[PathBuf], with_icu: bool) {
+    let mut shim = cc::Build::new();
+
+    // Only the system headers: nothing of vendor/re2 is compiled against the library
+    for inc in includes {
+        shim.include(inc);
+    }
+
+    let compiler = shim.get_compiler();
+    let is_msvc = compiler.is_like_msvc();
+    add_common_defines(&mut shim, is_msvc);
+    shim.define("RE2_RS_SYSTEM_RE2", None);
+    if with_icu {
+        shim.define("RE2_RS_ICU_ENGINE", None);
+    }
+
+    shim.file("src/c-bindings.cc");
+
+    shim.compile("re2_shim");
+}
+
+fn is_icu_lib(name: &str) -> bool {
+    name.starts_with("icu") || name.starts_with("sicu")
+}
+
+fn has_static_archive(cfg: &IcuConfig, lib: &str) -> bool {
+    let file = if cfg!(target_env = "msvc") {
+        format!("{}.lib", lib)
+    } else {
+        format!("lib{}.a", lib)
+    };
+    cfg.link_paths.iter().any(|dir| dir.join(&file).exists())
+}
+
+fn build_re2_icu(vendor: &Path, absl_includes: &[PathBuf], icu: &IcuConfig) {
+    let mut re2 = cc::Build::new();
+
+    re2.include(vendor.join("re2"));
+    for inc in absl_includes {
+        re2.include(inc);
+    }
+
+    for inc in &icu.include_paths {
+        re2.include(inc);
+        println!("cargo:warning=Using ICU include path {}", inc.display());
+    }
 
     let compiler = re2.get_compiler();
     let is_msvc = compiler.is_like_msvc();
-    add_common_defines(&mut re2, is_msvc, with_icu);
+    add_common_defines(&mut re2, is_msvc);
+    re2.define("RE2_USE_ICU", Some("1"));
+    if cfg!(feature = "icu-static") {
+        // No dllimport on ICU's declarations
+        re2.define("U_STATIC_IMPLEMENTATION", None);
+    }
+    // Renames namespace re2 (and with it every symbol of this copy) to re2_icu
+    re2.define("re2", Some("re2_icu"));
+
+    add_re2_sources(&mut re2, "RE2 ICU", &[]);
+    re2.file("src/icu-engine.cc");
 
+    re2.compile("re2_icu_core");
+}
+
+fn add_re2_sources(build: &mut cc::Build, label: &str, skip: &[&str]) {
     for entry in glob::glob("../vendor/re2/*.cc").unwrap() {
         let file = entry.unwrap();
-        println!("RE2 {}", file.display());
-        re2.file(&file);
+        if file.file_name().is_some_and(|name| skip.iter().any(|s| name == *s)) {
+            continue;
+        }
+        println!("{} {}", label, file.display());
+        build.file(&file);
     }
     for entry in glob::glob("../vendor/re2/util/*.cc").unwrap() {
         let file = entry.unwrap();
-        println!("RE2 UTIL {}", file.display());
-        re2.file(&file);
+        println!("{} UTIL {}", label, file.display());
+        build.file(&file);
     }
-
-    re2.file("src/c-bindings.cc");
-
-    re2.compile("re2_core");
 }
 
-fn add_common_defines(build: &mut cc::Build, is_msvc: bool, with_icu: bool) {
+fn add_common_defines(build: &mut cc::Build, is_msvc: bool) {
     build.cpp(true);
+    if cxx_stdlib_mode() != CxxStdlib::Dynamic {
+        // Linked once by link_cxx_stdlib instead
+        build.cpp_link_stdlib(None);
+    }
     if is_msvc {
         build.flag("/std:c++17").flag("/EHsc");
         build.define("NOMINMAX", None);
     } else {
         build.flag_if_supported("-std=c++17");
     }
-    if with_icu {
-        build.define("RE2_WITH_ICU", None);
-        build.define("RE2_USE_ICU", Some("1"));
+    add_tuning_flags(build, is_msvc);
+}
+
+/// Optimisation, LTO and sanitizer flags shared by every C++ build (see step 8)
+fn add_tuning_flags(build: &mut cc::Build, is_msvc: bool) {
+    if cfg!(feature = "cxx-release") {
+        build.opt_level(3);
+        build.define("NDEBUG", None);
+    }
+
+    if cfg!(feature = "cxx-lto") {
+        if !build.get_compiler().is_like_clang() {
+            panic!("feature `cxx-lto` needs clang (set CC=clang CXX=clang++) so rustc's LLVM can read the bitcode");
+        }
+        build.flag("-flto=thin");
+    }
+
+    println!("cargo:rerun-if-env-changed=RE2_RS_SANITIZE");
+    if let Ok(sanitizers) = env::var("RE2_RS_SANITIZE") {
+        let sanitizers: Vec<&str> = sanitizers.split(',').map(str::trim).filter(|s| !s.is_empty()).collect();
+        for sanitizer in &sanitizers {
+            if !matches!(*sanitizer, "address" | "undefined") {
+                panic!("RE2_RS_SANITIZE: unsupported sanitizer `{}` (expected address and/or undefined)", sanitizer);
+            }
+        }
+        if is_msvc {
+            if sanitizers.contains(&"address") {
+                build.flag("/fsanitize=address");
+            }
+        } else if !sanitizers.is_empty() {
+            build.flag(format!("-fsanitize={}", sanitizers.join(",")));
+            build.flag("-fno-omit-frame-pointer");
+            if sanitizers.contains(&"undefined") {
+                // rustc links no UBSan runtime, so report by trapping instead
+                build.flag("-fsanitize-trap=undefined");
+            }
+        }
     }
 }
 
-fn ensure_icu_libs(cfg: &mut IcuConfig) {
-    let required: &[&str] = if cfg!(target_os = "windows") {
+fn ensure_icu_libs(cfg: &mut IcuConfig, static_icu: bool) {
+    let required: &[&str] = if static_icu && cfg!(target_os = "windows") {
+        // Static ICU libs are prefixed with `s` on MSVC
+        &["sicuin", "sicuuc", "sicudt"]
+    } else if cfg!(target_os = "windows") {
         // ICU lib names for MSVC builds
         &["icuin", "icuuc", "icudt", "icutu"]
     } else {
@@ -342,3 +746,206 @@ fn ensure_icu_libs(cfg: &mut IcuConfig) {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CxxStdlib {
+    Dynamic,
+    Static,
+    None,
+}
+
+fn cxx_stdlib_mode() -> CxxStdlib {
+    println!("cargo:rerun-if-env-changed=RE2_RS_CXX_STDLIB");
+    match env::var("RE2_RS_CXX_STDLIB").as_deref() {
+        Ok("dynamic") => CxxStdlib::Dynamic,
+        Ok("static") => CxxStdlib::Static,
+        Ok("none") => CxxStdlib::None,
+        Ok(other) => panic!("RE2_RS_CXX_STDLIB={} is not one of dynamic, static, none", other),
+        Err(_) if env::var("CARGO_CFG_TARGET_ENV").as_deref() == Ok("musl") => CxxStdlib::Static,
+        Err(_) => CxxStdlib::Dynamic,
+    }
+}
+
+/// The C++ runtime `cc` would link: CXXSTDLIB if set, else its per-target default
+fn cxx_stdlib_name() -> Option<String> {
+    let target = env::var("TARGET").unwrap();
+    for var in [format!("CXXSTDLIB_{}", target.replace('-', "_")), "CXXSTDLIB".to_string()] {
+        println!("cargo:rerun-if-env-changed={}", var);
+        if let Ok(name) = env::var(&var) {
+            return (!name.is_empty()).then_some(name);
+        }
+    }
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+    let vendor = env::var("CARGO_CFG_TARGET_VENDOR").unwrap();
+    if env::var("CARGO_CFG_TARGET_ENV").as_deref() == Ok("msvc") {
+        None
+    } else if vendor == "apple" || os == "freebsd" || os == "openbsd" {
+        Some("c++".into())
+    } else {
+        Some("stdc++".into())
+    }
+}
+
+/// Emitted after every archive that needs the runtime, for single-pass linkers
+fn link_cxx_stdlib() {
+    if cxx_stdlib_mode() != CxxStdlib::Static {
+        return;
+    }
+    let Some(name) = cxx_stdlib_name() else {
+        return;
+    };
+
+    // Ask the C++ compiler where its archive lives, as the linker's default paths are
+    // not searched when rustc bundles a static library
+    let compiler = cc::Build::new().cpp(true).get_compiler();
+    let archive = format!("lib{}.a", name);
+    let output = Command::new(compiler.path())
+        .arg(format!("-print-file-name={}", archive))
+        .output()
+        .expect("Couldn't run the C++ compiler to locate its runtime");
+    let path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    match path.parent() {
+        Some(dir) if path.is_absolute() && path.exists() => {
+            println!("cargo:rustc-link-search=native={}", dir.display());
+        }
+        _ => panic!(
+            "{} not found by {}; install the static C++ runtime or set RE2_RS_CXX_STDLIB=dynamic",
+            archive,
+            compiler.path().display()
+        ),
+    }
+    println!("cargo:rustc-link-lib=static={}", name);
+}
+
+/// Written last into a prebuilt directory: the hash it was built for and its archives
+const PREBUILT_STAMP: &str = "re2-rs-prebuilt.txt";
+
+/// <RE2_RS_PREBUILT_DIR>/<hash> for this build, if the cache is enabled
+fn prebuilt_dir(vendor: &Path, with_icu: bool) -> Option<PathBuf> {
+    println!("cargo:rerun-if-env-changed=RE2_RS_PREBUILT_DIR");
+    let root = env::var_os("RE2_RS_PREBUILT_DIR").filter(|d| !d.is_empty())?;
+    Some(PathBuf::from(root).join(prebuilt_hash(vendor, with_icu)))
+}
+
+/// FNV-1a over the sources the archives are built from and the settings they are built with
+fn prebuilt_hash(vendor: &Path, with_icu: bool) -> String {
+    let mut files = Vec::new();
+    collect_files(&vendor.join("re2"), &mut files);
+    collect_files(&vendor.join("abseil-cpp"), &mut files);
+    files.sort();
+    files.extend(
+        ["src/c-bindings.cc", "src/c-bindings.h", "src/footprint.cc", "src/footprint.h", ABSL_MANIFEST]
+            .map(PathBuf::from),
+    );
+
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    let mut feed = |bytes: &[u8]| {
+        for b in bytes.iter().chain([0u8].iter()) {
+            hash ^= u64::from(*b);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    for file in &files {
+        feed(file.to_string_lossy().replace('\\', "/").as_bytes());
+        feed(&fs::read(file).unwrap_or_else(|e| panic!("Couldn't read {}: {}", file.display(), e)));
+    }
+
+    let mut reference = cc::Build::new();
+    let is_msvc = reference.get_compiler().is_like_msvc();
+    add_common_defines(&mut reference, is_msvc);
+    let compiler = reference.get_compiler();
+    feed(env::var("TARGET").unwrap().as_bytes());
+    feed(compiler.path().to_string_lossy().as_bytes());
+    for arg in compiler.args() {
+        feed(arg.to_string_lossy().as_bytes());
+    }
+    // re2_core's shim is compiled with RE2_RS_ICU_ENGINE in ICU builds
+    feed(if with_icu { b"icu" } else { b"plain" });
+
+    format!("{:016x}", hash)
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap_or_else(|e| panic!("Couldn't read {}: {}", dir.display(), e)) {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+fn archive_name(lib: &str) -> String {
+    if env::var("CARGO_CFG_TARGET_ENV").as_deref() == Ok("msvc") {
+        format!("{}.lib", lib)
+    } else {
+        format!("lib{}.a", lib)
+    }
+}
+
+/// Link the archives in `dir` if its stamp matches, returning false to build them instead
+fn link_prebuilt(dir: &Path) -> bool {
+    let Ok(stamp) = fs::read_to_string(dir.join(PREBUILT_STAMP)) else {
+        return false;
+    };
+    let hash = dir.file_name().unwrap().to_string_lossy();
+    let mut stamp_hash = None;
+    let mut libs = Vec::new();
+    for line in stamp.lines() {
+        if let Some(value) = line.strip_prefix("hash=") {
+            stamp_hash = Some(value);
+        } else if let Some(value) = line.strip_prefix("libs=") {
+            libs = value.split_whitespace().collect();
+        }
+    }
+    if stamp_hash != Some(&*hash) || libs.is_empty() || !libs.iter().all(|l| dir.join(archive_name(l)).exists()) {
+        println!("cargo:warning=Ignoring invalid prebuilt archives in {} (delete it to store a fresh set)", dir.display());
+        return false;
+    }
+
+    println!("cargo:rustc-link-search=native={}", dir.display());
+    for lib in &libs {
+        println!("cargo:rustc-link-lib=static={}", lib);
+    }
+    // Normally emitted by `cc` when it compiles
+    if cxx_stdlib_mode() == CxxStdlib::Dynamic
+        && let Some(name) = cxx_stdlib_name()
+    {
+        println!("cargo:rustc-link-lib={}", name);
+    }
+    true
+}
+
+/// Copy freshly built archives into `dir`. Best effort: a failure only costs the next build time.
+fn store_prebuilt(dir: &Path, libs: &[String]) {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    // Fill a private directory and rename it into place, so concurrent builds never see a
+    // partial set. Whichever rename lands first wins; a set already in place is kept, as
+    // another build may be linking against it.
+    if dir.exists() {
+        println!("Prebuilt archives already in {}", dir.display());
+        return;
+    }
+    let staging = dir.with_extension(format!("tmp-{}", std::process::id()));
+    let result = (|| -> std::io::Result<()> {
+        fs::create_dir_all(&staging)?;
+        for lib in libs {
+            fs::copy(out_dir.join(archive_name(lib)), staging.join(archive_name(lib)))?;
+        }
+        let hash = dir.file_name().unwrap().to_string_lossy();
+        fs::write(staging.join(PREBUILT_STAMP), format!("hash={}\nlibs={}\n", hash, libs.join(" ")))?;
+        match fs::rename(&staging, dir) {
+            Err(e) if matches!(e.kind(), ErrorKind::AlreadyExists | ErrorKind::DirectoryNotEmpty) => {
+                fs::remove_dir_all(&staging)
+            }
+            other => other,
+        }
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&staging);
+        println!("cargo:warning=Couldn't store prebuilt archives in {}: {}", dir.display(), e);
+    } else {
+        println!("Stored prebuilt archives in {}", dir.display());
+    }
+}
@@ -1,10 +1,24 @@
 /* automatically generated by rust-bindgen 0.72.1 */
 
+pub const RE2_STATUS_OK: u32 = 0;
+pub const RE2_STATUS_OUT_OF_MEMORY: u32 = 1;
+pub const RE2_STATUS_INTERNAL: u32 = 2;
+pub const RE2_ENGINE_PLAIN: u32 = 0;
+pub const RE2_ENGINE_ICU: u32 = 1;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct RE2Wrapper {
     _unused: [u8; 0],
 }
+unsafe extern "C" {
+    pub fn re2_last_status() -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_last_status_message(
+        msg_ptr: *mut *const ::std::os::raw::c_char,
+        msg_len: *mut usize,
+    );
+}
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct re2_span_t {
@@ -52,6 +66,24 @@ unsafe extern "C" {
 unsafe extern "C" {
     pub fn re2_options_set_perl_classes(o: *mut RE2Options, yes: ::std::os::raw::c_int);
 }
+unsafe extern "C" {
+    pub fn re2_options_set_log_errors(o: *mut RE2Options, yes: ::std::os::raw::c_int);
+}
+unsafe extern "C" {
+    pub fn re2_options_set_max_mem(o: *mut RE2Options, max_mem: i64);
+}
+unsafe extern "C" {
+    pub fn re2_options_set_engine(o: *mut RE2Options, engine: ::std::os::raw::c_int);
+}
+unsafe extern "C" {
+    pub fn re2_options_set_unicode_perl_classes(o: *mut RE2Options, enabled: ::std::os::raw::c_int);
+}
+unsafe extern "C" {
+    pub fn re2_options_max_mem(o: *const RE2Options) -> i64;
+}
+unsafe extern "C" {
+    pub fn re2_options_clone(o: *const RE2Options) -> *mut RE2Options;
+}
 unsafe extern "C" {
     pub fn re2_new_with_options(
         pattern: *const ::std::os::raw::c_char,
@@ -74,6 +106,13 @@ unsafe extern "C" {
         err_len: *mut usize,
     );
 }
+unsafe extern "C" {
+    pub fn re2_pattern(
+        re2: *const RE2Wrapper,
+        pattern_ptr: *mut *const ::std::os::raw::c_char,
+        pattern_len: *mut usize,
+    );
+}
 unsafe extern "C" {
     pub fn re2_full_match(
         re2: *const RE2Wrapper,
@@ -135,6 +174,310 @@ unsafe extern "C" {
 unsafe extern "C" {
     pub fn re2_group_count(re2: *const RE2Wrapper) -> ::std::os::raw::c_int;
 }
+unsafe extern "C" {
+    pub fn re2_error_code(re2: *const RE2Wrapper) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_program_size(re2: *const RE2Wrapper) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_max_repeat(re2: *const RE2Wrapper) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_approx_heap_bytes(re2: *const RE2Wrapper) -> usize;
+}
+unsafe extern "C" {
+    pub fn re2_max_heap_bytes(re2: *const RE2Wrapper) -> usize;
+}
 unsafe extern "C" {
     pub fn re2_has_icu() -> ::std::os::raw::c_int;
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct RE2SetWrapper {
+    _unused: [u8; 0],
+}
+unsafe extern "C" {
+    pub fn re2_set_new(opts: *const RE2Options, anchor: ::std::os::raw::c_int) -> *mut RE2SetWrapper;
+}
+unsafe extern "C" {
+    pub fn re2_set_delete(set: *mut RE2SetWrapper);
+}
+unsafe extern "C" {
+    pub fn re2_set_add(
+        set: *mut RE2SetWrapper,
+        pattern: *const ::std::os::raw::c_char,
+        pattern_len: usize,
+        err_ptr: *mut *const ::std::os::raw::c_char,
+        err_len: *mut usize,
+    ) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_set_size(set: *const RE2SetWrapper) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_set_compile(set: *mut RE2SetWrapper) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_set_match(
+        set: *const RE2SetWrapper,
+        text: *const ::std::os::raw::c_char,
+        text_len: usize,
+        out_indices: *mut ::std::os::raw::c_int,
+        out_len: usize,
+        written: *mut usize,
+        error_kind: *mut ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct RE2FilteredWrapper {
+    _unused: [u8; 0],
+}
+unsafe extern "C" {
+    pub fn re2_filtered_new(min_atom_len: ::std::os::raw::c_int) -> *mut RE2FilteredWrapper;
+}
+unsafe extern "C" {
+    pub fn re2_filtered_delete(f: *mut RE2FilteredWrapper);
+}
+unsafe extern "C" {
+    pub fn re2_filtered_add(
+        f: *mut RE2FilteredWrapper,
+        pattern: *const ::std::os::raw::c_char,
+        pattern_len: usize,
+        opts: *const RE2Options,
+        err_ptr: *mut *const ::std::os::raw::c_char,
+        err_len: *mut usize,
+    ) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_filtered_size(f: *const RE2FilteredWrapper) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_filtered_compile(f: *mut RE2FilteredWrapper);
+}
+unsafe extern "C" {
+    pub fn re2_filtered_num_atoms(f: *const RE2FilteredWrapper) -> usize;
+}
+unsafe extern "C" {
+    pub fn re2_filtered_atom(
+        f: *const RE2FilteredWrapper,
+        i: usize,
+        atom_ptr: *mut *const ::std::os::raw::c_char,
+        atom_len: *mut usize,
+    );
+}
+unsafe extern "C" {
+    pub fn re2_filtered_slow_first_match(
+        f: *const RE2FilteredWrapper,
+        text: *const ::std::os::raw::c_char,
+        text_len: usize,
+    ) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_filtered_first_match(
+        f: *const RE2FilteredWrapper,
+        text: *const ::std::os::raw::c_char,
+        text_len: usize,
+        atoms: *const ::std::os::raw::c_int,
+        atoms_len: usize,
+    ) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_filtered_all_matches(
+        f: *const RE2FilteredWrapper,
+        text: *const ::std::os::raw::c_char,
+        text_len: usize,
+        atoms: *const ::std::os::raw::c_int,
+        atoms_len: usize,
+        out_ids: *mut ::std::os::raw::c_int,
+        out_len: usize,
+        written: *mut usize,
+    ) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_filtered_all_potentials(
+        f: *const RE2FilteredWrapper,
+        atoms: *const ::std::os::raw::c_int,
+        atoms_len: usize,
+        out_ids: *mut ::std::os::raw::c_int,
+        out_len: usize,
+        written: *mut usize,
+    );
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct RE2Prefilter {
+    _unused: [u8; 0],
+}
+unsafe extern "C" {
+    pub fn re2_prefilter_from_re2(re2: *const RE2Wrapper) -> *mut RE2Prefilter;
+}
+unsafe extern "C" {
+    pub fn re2_prefilter_delete(pf: *mut RE2Prefilter);
+}
+unsafe extern "C" {
+    pub fn re2_prefilter_op(pf: *const RE2Prefilter) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_prefilter_atom(
+        pf: *const RE2Prefilter,
+        atom_ptr: *mut *const ::std::os::raw::c_char,
+        atom_len: *mut usize,
+    );
+}
+unsafe extern "C" {
+    pub fn re2_prefilter_num_subs(pf: *const RE2Prefilter) -> usize;
+}
+unsafe extern "C" {
+    pub fn re2_prefilter_sub(pf: *const RE2Prefilter, i: usize) -> *const RE2Prefilter;
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct RE2String {
+    _unused: [u8; 0],
+}
+unsafe extern "C" {
+    pub fn re2_string_data(s: *const RE2String) -> *const ::std::os::raw::c_char;
+}
+unsafe extern "C" {
+    pub fn re2_string_len(s: *const RE2String) -> usize;
+}
+unsafe extern "C" {
+    pub fn re2_string_delete(s: *mut RE2String);
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct RE2Regexp {
+    _unused: [u8; 0],
+}
+unsafe extern "C" {
+    pub fn re2_regexp_parse(
+        pattern: *const ::std::os::raw::c_char,
+        pattern_len: usize,
+        flags: ::std::os::raw::c_int,
+        err: *mut *mut RE2String,
+    ) -> *mut RE2Regexp;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_delete(re: *mut RE2Regexp);
+}
+unsafe extern "C" {
+    pub fn re2_regexp_op(re: *const RE2Regexp) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_flags(re: *const RE2Regexp) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_nsub(re: *const RE2Regexp) -> usize;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_sub(re: *const RE2Regexp, i: usize) -> *const RE2Regexp;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_min(re: *const RE2Regexp) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_max(re: *const RE2Regexp) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_cap(re: *const RE2Regexp) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_name(
+        re: *const RE2Regexp,
+        name_ptr: *mut *const ::std::os::raw::c_char,
+        name_len: *mut usize,
+    );
+}
+unsafe extern "C" {
+    pub fn re2_regexp_rune(re: *const RE2Regexp) -> i32;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_runes(re: *const RE2Regexp, runes: *mut *const i32) -> usize;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_num_ranges(re: *const RE2Regexp) -> usize;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_range(re: *const RE2Regexp, i: usize, lo: *mut i32, hi: *mut i32);
+}
+unsafe extern "C" {
+    pub fn re2_regexp_match_id(re: *const RE2Regexp) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_mimics_pcre(re: *const RE2Regexp) -> ::std::os::raw::c_int;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_canonical(
+        pattern: *const ::std::os::raw::c_char,
+        pattern_len: usize,
+        flags: ::std::os::raw::c_int,
+        err: *mut *mut RE2String,
+    ) -> *mut RE2String;
+}
+unsafe extern "C" {
+    pub fn re2_regexp_simplify(
+        pattern: *const ::std::os::raw::c_char,
+        pattern_len: usize,
+        flags: ::std::os::raw::c_int,
+        err: *mut *mut RE2String,
+    ) -> *mut RE2String;
+}
+unsafe extern "C" {
+    pub fn re2_required_prefix(
+        re2: *const RE2Wrapper,
+        foldcase: *mut ::std::os::raw::c_int,
+    ) -> *mut RE2String;
+}
+unsafe extern "C" {
+    pub fn re2_prog_dump(re2: *const RE2Wrapper) -> *mut RE2String;
+}
+unsafe extern "C" {
+    pub fn re2_prog_dump_unanchored(re2: *const RE2Wrapper) -> *mut RE2String;
+}
+unsafe extern "C" {
+    pub fn re2_prog_dump_byte_map(re2: *const RE2Wrapper) -> *mut RE2String;
+}
+unsafe extern "C" {
+    pub fn re2_prog_dot(re2: *const RE2Wrapper) -> *mut RE2String;
+}
+pub type RE2DFAStateCacheResetCallback = ::std::option::Option<
+    unsafe extern "C" fn(
+        state_budget: i64,
+        state_cache_size: usize,
+        pattern: *const ::std::os::raw::c_char,
+        pattern_len: usize,
+    ),
+>;
+pub type RE2DFASearchFailureCallback = ::std::option::Option<
+    unsafe extern "C" fn(pattern: *const ::std::os::raw::c_char, pattern_len: usize),
+>;
+unsafe extern "C" {
+    pub fn re2_set_dfa_state_cache_reset_hook(cb: RE2DFAStateCacheResetCallback);
+}
+unsafe extern "C" {
+    pub fn re2_set_dfa_search_failure_hook(cb: RE2DFASearchFailureCallback);
+}
+pub type RE2LogCallback = ::std::option::Option<
+    unsafe extern "C" fn(
+        severity: ::std::os::raw::c_int,
+        file: *const ::std::os::raw::c_char,
+        file_len: usize,
+        line: ::std::os::raw::c_int,
+        msg: *const ::std::os::raw::c_char,
+        msg_len: usize,
+    ),
+>;
+unsafe extern "C" {
+    pub fn re2_set_log_callback(cb: RE2LogCallback);
+}
+unsafe extern "C" {
+    pub fn re2_abseil_version() -> *mut RE2String;
+}
+unsafe extern "C" {
+    pub fn re2_icu_version() -> *mut RE2String;
+}
+unsafe extern "C" {
+    pub fn re2_icu_unicode_version() -> *mut RE2String;
+}
@@ -1,110 +1,763 @@
 // src/c-bindings.cc
 #include "c-bindings.h"
+#ifdef RE2_RS_ICU_ENGINE
+#include "icu-engine.h"
+#endif
+#ifndef RE2_RS_SYSTEM_RE2
+#include "footprint.h"
+#endif
+#include <absl/base/config.h>
+#include <absl/log/absl_log.h>
+#include <absl/log/globals.h>
+#include <absl/log/initialize.h>
+#include <absl/log/internal/globals.h>
+#include <absl/log/log_entry.h>
+#include <absl/log/log_sink.h>
+#include <absl/log/log_sink_registry.h>
+#include <absl/strings/str_cat.h>
+#include <absl/strings/str_format.h>
+#include <re2/filtered_re2.h>
 #include <re2/re2.h>
+#include <re2/set.h>
+#ifndef RE2_RS_SYSTEM_RE2
+#include <re2/prefilter.h>
+#include <re2/prog.h>
+#include <re2/regexp.h>
+#endif
+#include <algorithm>
+#include <atomic>
+#include <memory>
+#include <mutex>
+#include <new>
+#include <stdexcept>
+#include <string>
+#include <unordered_map>
 #include <vector>
 #include <cstring>
 
+// Every exported function runs inside guarded(): a C++ exception must not unwind
+// into Rust. The failure is recorded per thread for re2_last_status and the
+// function returns its usual failure value instead.
+static thread_local int last_status = RE2_STATUS_OK;
+static thread_local std::string last_status_message;
+
+static void record_exception(int status, const char* what) {
+    last_status = status;
+    try {
+        last_status_message = what ? what : "";
+    } catch (...) {
+        last_status_message.clear();
+    }
+}
+
+template <typename T, typename F>
+static T guarded(T on_error, F&& body) noexcept {
+    last_status = RE2_STATUS_OK;
+    try {
+        return body();
+    } catch (const std::bad_alloc& e) {
+        record_exception(RE2_STATUS_OUT_OF_MEMORY, e.what());
+    } catch (const std::exception& e) {
+        record_exception(RE2_STATUS_INTERNAL, e.what());
+    } catch (...) {
+        record_exception(RE2_STATUS_INTERNAL, "unknown C++ exception");
+    }
+    return on_error;
+}
+
+template <typename F>
+static void guarded_void(F&& body) noexcept {
+    guarded<int>(0, [&] {
+        body();
+        return 0;
+    });
+}
+
+#ifdef RE2_RS_SYSTEM_RE2
+// Against a system RE2 only the public headers are used: the internal ones
+// (regexp.h, prog.h, prefilter.h) are not installed, and vendor/re2's need not match
+// the library's layout. What is built on them fails with this instead.
+[[noreturn]] static void needs_vendored_re2(const char* what) {
+    throw std::logic_error(std::string(what) + " needs the vendored RE2 (built with system-re2)");
+}
+#endif
+
 struct RE2Wrapper {
     re2::RE2 re;
-    explicit RE2Wrapper(const re2::StringPiece& pat, re2::RE2::Options opts = re2::RE2::Options())
-        : re(pat, opts) {}
+    // The pattern as written; re.pattern() is the text RE2 compiled, after any Unicode
+    // Perl classes rewrite or ICU translation
+    std::string pattern;
+    // Set when the shim rejected the pattern; `re` is then an empty placeholder
+    std::string engine_error;
+    int engine_error_code = re2::RE2::NoError;
+    // Whether `re` is in written_patterns()
+    bool registered = false;
+    RE2Wrapper(re2::StringPiece written, const re2::StringPiece& pat, re2::RE2::Options opts)
+        : re(pat, opts), pattern(written) {}
+    RE2Wrapper(re2::StringPiece written, std::string error, int code)
+        : re(""), pattern(written), engine_error(std::move(error)), engine_error_code(code) {}
+    ~RE2Wrapper();
+    bool ok() const { return engine_error.empty() && re.ok(); }
+    const std::string& error() const { return engine_error.empty() ? re.error() : engine_error; }
 };
 
 struct RE2Options {
     re2::RE2::Options opts;
+    bool icu = false;
+    bool unicode_perl_classes = false;
+};
+
+// ----- Unicode Perl classes -----
+// With RE2Options::unicode_perl_classes, \d \s \w and their negations are rewritten to
+// Unicode tables before RE2 parses the pattern: \d is \p{Nd}, \s is Unicode White_Space
+// and \w approximates UTS #18 word characters with general categories (RE2's tables have
+// no binary properties such as Alphabetic). \b and \B cannot follow: the matchers test
+// word boundaries against a fixed ASCII table, not a class a rewrite could replace, and
+// RE2 has no lookaround to spell one with. They are rejected rather than left ASCII.
+static const char kUnicodeDigit[] = "\\p{Nd}";
+static const char kUnicodeNotDigit[] = "\\P{Nd}";
+static const char kUnicodeSpace[] = "\\t\\n\\x0B\\f\\r\\x{85}\\p{Z}";
+static const char kUnicodeWord[] = "\\p{L}\\p{M}\\p{Nd}\\p{Nl}\\p{Pc}\\x{200C}\\x{200D}";
+
+// The code points in (or with `negate`, outside) the class `body`, the inside of a [...],
+// as a range list. Used where the short forms do not parse: \S and \W inside a class,
+// where a [^...] cannot nest, and \p{..} without Regexp::UnicodeGroups (POSIX syntax).
+static std::string class_ranges(const char* body, bool negate) {
+#ifdef RE2_RS_SYSTEM_RE2
+    (void)body;
+    (void)negate;
+    needs_vendored_re2("unicode_perl_classes under POSIX syntax or for \\S and \\W inside [...]");
+#else
+    re2::RegexpStatus status;
+    re2::Regexp* re = re2::Regexp::Parse(std::string("[") + body + "]", re2::Regexp::LikePerl, &status);
+    if (!re || re->op() != re2::kRegexpCharClass)
+        throw std::logic_error("unicode_perl_classes: bad class " + std::string(body));
+    re2::CharClass* cc = negate ? re->cc()->Negate() : re->cc();
+    std::string out;
+    for (re2::CharClass::iterator it = cc->begin(); it != cc->end(); ++it) {
+        out += "\\x{" + absl::StrFormat("%X", it->lo) + "}";
+        if (it->hi > it->lo) out += "-\\x{" + absl::StrFormat("%X", it->hi) + "}";
+    }
+    if (negate) cc->Delete();
+    re->Decref();
+    return out;
+#endif
+}
+
+// class_ranges() of the Perl class escape `e` (one of dDsSwW), computed on first use
+static const std::string& unicode_ranges(char e) {
+    switch (e) {
+        case 'd': { static const std::string r = class_ranges(kUnicodeDigit, false); return r; }
+        case 'D': { static const std::string r = class_ranges(kUnicodeDigit, true); return r; }
+        case 's': { static const std::string r = class_ranges(kUnicodeSpace, false); return r; }
+        case 'S': { static const std::string r = class_ranges(kUnicodeSpace, true); return r; }
+        case 'w': { static const std::string r = class_ranges(kUnicodeWord, false); return r; }
+        default:  { static const std::string r = class_ranges(kUnicodeWord, true); return r; }
+    }
+}
+
+// Replacement for the Perl class escape `e` (one of dDsSwW)
+static std::string unicode_class(char e, bool in_class, bool unicode_groups) {
+    const char* body = (e == 'd' || e == 'D') ? kUnicodeDigit : (e == 's' || e == 'S') ? kUnicodeSpace : kUnicodeWord;
+    bool negated = e == 'D' || e == 'S' || e == 'W';
+
+    if (!unicode_groups) {
+        return in_class ? unicode_ranges(e) : absl::StrCat("[", unicode_ranges(e), "]");
+    }
+    if (e == 'd') return kUnicodeDigit;
+    if (e == 'D') return kUnicodeNotDigit;
+    if (in_class) return negated ? unicode_ranges(e) : body;
+    return absl::StrCat(negated ? "[^" : "[", body, "]");
+}
+
+// Rewrite `p` into *out. Returns false and sets *error for a \b or \B that RE2 would
+// read as a word boundary (`perl_b`).
+static bool unicode_perl_classes(re2::StringPiece p, bool unicode_groups, bool perl_b, std::string* rewritten,
+                                 std::string* error) {
+    std::string& out = *rewritten;
+    out.clear();
+    out.reserve(p.size());
+    bool in_class = false;
+    bool quoted = false;  // inside \Q...\E
+    for (size_t i = 0; i < p.size(); ++i) {
+        char c = p[i];
+        if (quoted) {
+            if (c == '\\' && i + 1 < p.size() && p[i + 1] == 'E') {
+                quoted = false;
+                out += p[i++];
+            }
+            out += p[i];
+            continue;
+        }
+        if (c == '\\' && i + 1 < p.size()) {
+            char e = p[++i];
+            if (e != '\0' && std::strchr("dDsSwW", e)) {
+                out += unicode_class(e, in_class, unicode_groups);
+            } else if ((e == 'b' || e == 'B') && perl_b && !in_class) {
+                *error = absl::StrCat("word boundary has no Unicode form with unicode_perl_classes: \\", std::string(1, e));
+                return false;
+            } else {
+                quoted = e == 'Q' && !in_class;
+                out += c;
+                out += e;
+            }
+            continue;
+        }
+        out += c;
+        if (!in_class && c == '[') {
+            in_class = true;
+            // A leading ] (after an optional ^) is a literal
+            if (i + 1 < p.size() && p[i + 1] == '^') out += p[++i];
+            if (i + 1 < p.size() && p[i + 1] == ']') out += p[++i];
+        } else if (in_class && c == '[' && i + 1 < p.size() && p[i + 1] == ':') {
+            // [:alpha:] and friends, copied through their closing :]
+            size_t end = p.find(":]", i + 2);
+            if (end != re2::StringPiece::npos) {
+                out.append(p.data() + i + 1, end + 2 - (i + 1));
+                i = end + 1;
+            }
+        } else if (in_class && c == ']') {
+            in_class = false;
+        }
+    }
+    return true;
+}
+
+// ----- Engines -----
+// A pattern for the ICU engine is parsed by icu-engine.cc and compiled by this (plain)
+// copy of RE2 from the printed tree, under options that parse that output back unchanged:
+// the printed tree already carries the case folding and needs Perl syntax.
+static re2::RE2::Options engine_options(re2::RE2::Options o, bool icu) {
+    if (icu) {
+        o.set_posix_syntax(false);
+        o.set_literal(false);
+        o.set_case_sensitive(true);
+    }
+    return o;
+}
+
+// Whether engine_pattern() rewrites Perl classes: only where RE2 itself would read \d as
+// one. Options::ParseFlags() sets PerlClasses (and PerlB, UnicodeGroups) unless
+// posix_syntax, and PerlClasses for perl_classes.
+static bool rewrites_perl_classes(bool unicode_classes, const re2::RE2::Options& o) {
+    return unicode_classes && (!o.posix_syntax() || o.perl_classes()) && !o.literal();
+}
+
+// Replace *pattern (kept in *storage) with its Unicode Perl classes rewrite if
+// `unicode_classes` is set, then with its ICU translation if `icu` is set. Returns false
+// and sets *error / *code if the rewrite met \b or \B, or if the ICU engine rejected the
+// pattern or is not built.
+static bool engine_pattern(bool icu, bool unicode_classes, const re2::RE2::Options& o, re2::StringPiece* pattern,
+                           std::string* storage, std::string* error, int* code) {
+    if (rewrites_perl_classes(unicode_classes, o)) {
+        bool perl_b = !o.posix_syntax() || o.word_boundary();
+        if (!unicode_perl_classes(*pattern, !o.posix_syntax(), perl_b, storage, error)) {
+            *code = re2::RE2::ErrorBadEscape;
+            return false;
+        }
+        *pattern = *storage;
+    }
+    if (!icu) return true;
+#ifdef RE2_RS_ICU_ENGINE
+    std::string translated;
+    if (!re2_rs::icu_translate(pattern->data(), pattern->size(), o.ParseFlags(), &translated, error, code))
+        return false;
+    *storage = std::move(translated);
+    *pattern = *storage;
+    return true;
+#else
+    (void)pattern;
+    (void)storage;
+    *error = "ICU engine not built (enable the icu feature)";
+    *code = re2::RE2::ErrorInternal;
+    return false;
+#endif
+}
+
+// ----- Patterns as written -----
+// RE2 only knows the text it compiled, so errors, logs and the DFA hooks would otherwise
+// show a pattern's Unicode Perl classes rewrite or ICU translation instead of the pattern.
+
+// Options to compile a rewritten pattern with: its errors are logged by log_written_error
+static re2::RE2::Options compile_options(re2::RE2::Options o, bool icu, bool rewrite) {
+    o = engine_options(o, icu);
+    if (rewrite) o.set_log_errors(false);
+    return o;
+}
+
+static void log_written_error(const re2::RE2::Options& o, re2::StringPiece written, const std::string& error) {
+    if (o.log_errors()) ABSL_LOG(ERROR) << "Error parsing '" << written << "': " << error;
+}
+
+// Once the Unicode Perl classes rewrite of `written` failed to compile, replace *error and
+// *code with what `written` itself gives: RE2 quotes the offending part of the pattern.
+// Both are kept if `written` compiles, i.e. only the rewrite failed (e.g. on max_mem).
+static void written_error(bool icu, const re2::RE2::Options& o, re2::StringPiece written, std::string* error,
+                          int* code) {
+    std::string storage, engine_error;
+    int engine_code = re2::RE2::NoError;
+    if (!engine_pattern(icu, false, o, &written, &storage, &engine_error, &engine_code)) {
+        *error = std::move(engine_error);
+        *code = engine_code;
+        return;
+    }
+    re2::RE2 re(written, compile_options(o, icu, true));
+    if (re.ok()) return;
+    *error = re.error();
+    *code = re.error_code();
+}
+
+// RE2s whose compiled text differs from the pattern as written, for the DFA hooks
+static std::mutex written_patterns_mu;
+
+static std::unordered_map<const re2::RE2*, std::string>& written_patterns() {
+    static auto* patterns = new std::unordered_map<const re2::RE2*, std::string>();
+    return *patterns;
+}
+
+static void register_written(const re2::RE2* re, re2::StringPiece written) {
+    std::lock_guard<std::mutex> lock(written_patterns_mu);
+    written_patterns()[re] = std::string(written);
+}
+
+static void unregister_written(const re2::RE2* re) {
+    std::lock_guard<std::mutex> lock(written_patterns_mu);
+    written_patterns().erase(re);
+}
+
+RE2Wrapper::~RE2Wrapper() {
+    if (registered) unregister_written(&re);
+}
+
+struct RE2SetWrapper {
+    re2::RE2::Set set;
+    bool icu;
+    bool unicode_classes;
+    re2::RE2::Options opts;
+    std::string last_error;
+    RE2SetWrapper(const re2::RE2::Options& opts, bool icu, bool unicode_classes, re2::RE2::Anchor anchor)
+        : set(compile_options(opts, icu, rewrites_perl_classes(unicode_classes, opts)), anchor), icu(icu),
+          unicode_classes(unicode_classes), opts(opts) {}
+};
+
+struct RE2FilteredWrapper {
+    re2::FilteredRE2 filtered;
+    std::vector<std::string> atoms;
+    std::string last_error;
+    // Those of filtered's RE2s that are in written_patterns()
+    std::vector<const re2::RE2*> registered;
+    explicit RE2FilteredWrapper(int min_atom_len) : filtered(min_atom_len) {}
+    ~RE2FilteredWrapper() {
+        for (const re2::RE2* re : registered) unregister_written(re);
+    }
+};
+
+#ifndef RE2_RS_SYSTEM_RE2
+// RE2Prefilter is never defined; handles are re2::Prefilter nodes.
+static re2::Prefilter* as_prefilter(const RE2Prefilter* pf) {
+    return reinterpret_cast<re2::Prefilter*>(const_cast<RE2Prefilter*>(pf));
+}
+
+// Helpers shared by several exports. Exports must not call each other: the inner
+// guarded() would reset last_status and lose the outer call's error.
+static size_t prefilter_num_subs(const RE2Prefilter* pf) {
+    if (!pf) return 0;
+    re2::Prefilter* p = as_prefilter(pf);
+    if (p->op() != re2::Prefilter::AND && p->op() != re2::Prefilter::OR) return 0;
+    return p->subs()->size();
+}
+
+#endif
+
+struct RE2String {
+    std::string s;
+};
+
+static RE2String* new_string(std::string s) {
+    auto* out = new RE2String();
+    out->s = std::move(s);
+    return out;
+}
+
+#ifndef RE2_RS_SYSTEM_RE2
+// RE2Regexp is never defined; handles are re2::Regexp nodes.
+static re2::Regexp* as_regexp(const RE2Regexp* re) {
+    return reinterpret_cast<re2::Regexp*>(const_cast<RE2Regexp*>(re));
+}
+
+static int regexp_op(const RE2Regexp* re) {
+    return re ? static_cast<int>(as_regexp(re)->op()) : 0;
+}
+
+static size_t regexp_nsub(const RE2Regexp* re) {
+    return re ? static_cast<size_t>(as_regexp(re)->nsub()) : 0;
+}
+
+static size_t regexp_num_ranges(const RE2Regexp* re) {
+    if (regexp_op(re) != re2::kRegexpCharClass) return 0;
+    re2::CharClass* cc = as_regexp(re)->cc();
+    return cc ? static_cast<size_t>(cc->end() - cc->begin()) : 0;
+}
+
+static re2::Regexp* regexp_parse(const char* pattern, size_t pattern_len, int flags, RE2String** err) {
+    if (err) *err = nullptr;
+    re2::RegexpStatus status;
+    re2::Regexp* re = re2::Regexp::Parse(
+        re2::StringPiece(pattern, pattern_len),
+        static_cast<re2::Regexp::ParseFlags>(flags & re2::Regexp::AllParseFlags),
+        &status);
+    if (!re && err) *err = new_string(status.Text());
+    return re;
+}
+
+static bool contains_op(re2::Regexp* re, re2::RegexpOp a, re2::RegexpOp b) {
+    if (re->op() == a || re->op() == b) return true;
+    for (int i = 0; i < re->nsub(); ++i)
+        if (contains_op(re->sub()[i], a, b)) return true;
+    return false;
+}
+
+static bool contains_any_char(re2::Regexp* re) {
+    return contains_op(re, re2::kRegexpAnyChar, re2::kRegexpAnyChar);
+}
+
+// Largest count a repeat below `re` expands to, `outer` being the product of the
+// enclosing repeats' bounds (an unbounded {n,} counts as n). Like RE2's
+// RepetitionWalker, a bound of 0 ({0,} or {0}) is skipped rather than hiding the
+// counts below it.
+static int64_t max_repeat_count(re2::Regexp* re, int64_t outer) {
+    int64_t best = 0;
+    if (re->op() == re2::kRegexpRepeat) {
+        int64_t n = re->max() == -1 ? re->min() : re->max();
+        if (n > 0) outer = std::min<int64_t>(outer * n, INT32_MAX);
+        best = outer;
+    }
+    for (int i = 0; i < re->nsub(); ++i)
+        best = std::max(best, max_repeat_count(re->sub()[i], outer));
+    return best;
+}
+
+#endif
+
+// Rust callbacks for RE2's global hooks. Our trampolines stay installed once set
+// (RE2 has no way to unset a hook) and do nothing while the callback is NULL.
+static std::atomic<RE2DFAStateCacheResetCallback> dfa_state_cache_reset_cb{nullptr};
+static std::atomic<RE2DFASearchFailureCallback> dfa_search_failure_cb{nullptr};
+
+// Pattern of the RE2 currently matching on this thread, if RE2 tracks it
+static void hook_context_pattern(const char** ptr, size_t* len) {
+    *ptr = nullptr;
+    *len = 0;
+#ifdef RE2_HAVE_THREAD_LOCAL
+    if (const re2::RE2* re = re2::hooks::context) {
+        // Entries go when their RE2 is deleted, which cannot happen while it is matching
+        std::lock_guard<std::mutex> lock(written_patterns_mu);
+        auto it = written_patterns().find(re);
+        const std::string& pattern = it != written_patterns().end() ? it->second : re->pattern();
+        *ptr = pattern.data();
+        *len = pattern.size();
+    }
+#endif
+}
+
+static void dfa_state_cache_reset_trampoline(const re2::hooks::DFAStateCacheReset& info) {
+    RE2DFAStateCacheResetCallback cb = dfa_state_cache_reset_cb.load();
+    if (!cb) return;
+    const char* pattern;
+    size_t pattern_len;
+    hook_context_pattern(&pattern, &pattern_len);
+    cb(info.state_budget, info.state_cache_size, pattern, pattern_len);
+}
+
+static void dfa_search_failure_trampoline(const re2::hooks::DFASearchFailure&) {
+    RE2DFASearchFailureCallback cb = dfa_search_failure_cb.load();
+    if (!cb) return;
+    const char* pattern;
+    size_t pattern_len;
+    hook_context_pattern(&pattern, &pattern_len);
+    cb(pattern, pattern_len);
+}
+
+// Abseil sink forwarding RE2's log messages to a Rust callback. Registered once;
+// while a callback is set, Abseil's own stderr output is turned off, and the stderr
+// threshold in effect before is restored when it is cleared.
+static std::atomic<RE2LogCallback> log_cb{nullptr};
+static std::mutex log_cb_mu;
+static absl::LogSeverityAtLeast saved_stderr_threshold = absl::LogSeverityAtLeast::kInfo;
+
+class CallbackLogSink final : public absl::LogSink {
+  public:
+    void Send(const absl::LogEntry& entry) override {
+        RE2LogCallback cb = log_cb.load();
+        if (!cb) return;
+        absl::string_view file = entry.source_filename();
+        absl::string_view msg = entry.text_message();
+        cb(static_cast<int>(entry.log_severity()), file.data(), file.size(), entry.source_line(),
+           msg.data(), msg.size());
+    }
 };
 
+#ifndef RE2_RS_SYSTEM_RE2
+// Compile the same forward Prog RE2::Init builds (prog_ is private): the program
+// for the suffix after any ^literal prefix, with two thirds of max_mem.
+static std::unique_ptr<re2::Prog> compile_prog(const RE2Wrapper* re2) {
+    if (!re2 || !re2->re.ok()) return nullptr;
+    re2::Regexp* re = re2->re.Regexp();
+    if (!re) return nullptr;
+
+    std::string prefix;
+    bool foldcase = false;
+    re2::Regexp* suffix = nullptr;
+    if (!re->RequiredPrefix(&prefix, &foldcase, &suffix)) suffix = re->Incref();
+    std::unique_ptr<re2::Prog> prog(suffix->CompileToProg(re2->re.options().max_mem() * 2 / 3));
+    suffix->Decref();
+    return prog;
+}
+
+// Graphviz digraph of a flattened Prog: one node per instruction labelled with
+// Inst::Dump, solid edges to out(), dashed edges to the next entry of the same list.
+static std::string prog_to_dot(re2::Prog* prog) {
+    std::string dot = "digraph prog {\n  node [shape=box, fontname=monospace];\n";
+    dot += "  start [shape=point];\n  start -> " + std::to_string(prog->start()) + " [label=\"anchored\"];\n";
+    if (prog->start_unanchored() != prog->start()) {
+        dot += "  unanchored [shape=point];\n  unanchored -> " + std::to_string(prog->start_unanchored()) +
+               " [label=\"unanchored\"];\n";
+    }
+    for (int id = 0; id < prog->size(); id++) {
+        re2::Prog::Inst* ip = prog->inst(id);
+        std::string node = std::to_string(id);
+        dot += "  " + node + " [label=\"" + node + ". " + ip->Dump() + "\"];\n";
+        switch (ip->opcode()) {
+            case re2::kInstAlt:
+            case re2::kInstAltMatch:
+                dot += "  " + node + " -> " + std::to_string(ip->out()) + ";\n";
+                dot += "  " + node + " -> " + std::to_string(ip->out1()) + ";\n";
+                break;
+            case re2::kInstByteRange:
+            case re2::kInstCapture:
+            case re2::kInstEmptyWidth:
+            case re2::kInstNop:
+                dot += "  " + node + " -> " + std::to_string(ip->out()) + ";\n";
+                break;
+            default:
+                break;
+        }
+        if (!ip->last() && id + 1 < prog->size()) {
+            dot += "  " + node + " -> " + std::to_string(id + 1) + " [style=dashed];\n";
+        }
+    }
+    dot += "}\n";
+    return dot;
+}
+
+#endif
+
+// ----- Memory accounting -----
+static size_t base_bytes(const RE2Wrapper* re2) {
+    return sizeof(RE2Wrapper) + re2->pattern.size() + re2->re.pattern().size();
+}
+
+#ifdef RE2_RS_SYSTEM_RE2
+// prog.h is not installed with a system RE2 and its reverse program and DFAs are out of
+// reach, so this is only RE2's own estimate of the forward program's size (see
+// Compiler::Finish), with the sizes re2::Prog and re2::Prog::Inst have on 64-bit targets.
+static size_t program_bytes(const re2::RE2& re) {
+    int size = re.ProgramSize();
+    return size > 0 ? 432 + static_cast<size_t>(size) * 8 : 0;
+}
+#else
+using re2_rs::program_bytes;
+#endif
+
+#ifndef RE2_RS_SYSTEM_RE2
+// Regexp::ToString prints AnyChar as a bare "." (and newline-excluding classes
+// explicitly), so its output only round-trips under (?s). Likewise it prints the
+// multi-line anchors as bare ^ and $ (the one-line ones come out as (?-m:^), (?-m:$)
+// and \z), which only round-trips under (?m). Make whichever is needed explicit.
+static std::string to_pattern_string(re2::Regexp* re) {
+    std::string s = re->ToString();
+    bool any_char = contains_any_char(re);
+    bool line_anchor = contains_op(re, re2::kRegexpBeginLine, re2::kRegexpEndLine);
+    if (any_char && line_anchor) return "(?ms:" + s + ")";
+    if (line_anchor) return "(?m:" + s + ")";
+    if (any_char) return "(?s:" + s + ")";
+    return s;
+}
+
+#endif
+
+static re2::RE2::Anchor to_anchor(int anchor) {
+    switch (anchor) {
+        case 1:  return re2::RE2::ANCHOR_START;
+        case 2:  return re2::RE2::ANCHOR_BOTH;
+        default: return re2::RE2::UNANCHORED;
+    }
+}
+
 extern "C" {
 
 // ----- options -----
 RE2Options* re2_options_new() {
-    return new (std::nothrow) RE2Options();
+    return guarded<RE2Options*>(nullptr, [&]() -> RE2Options* {
+        return new RE2Options();
+    });
 }
 void re2_options_delete(RE2Options* o) {
-    delete o;
+    guarded_void([&] {
+        delete o;
+    });
 }
 void re2_options_set_case_sensitive(RE2Options* o, int sensitive) {
-    if (o) o->opts.set_case_sensitive(sensitive != 0);
+    guarded_void([&] {
+        if (o) o->opts.set_case_sensitive(sensitive != 0);
+    });
 }
 void re2_options_set_posix_syntax(RE2Options* o, int posix) {
-    if (o) o->opts.set_posix_syntax(posix != 0);
+    guarded_void([&] {
+        if (o) o->opts.set_posix_syntax(posix != 0);
+    });
 }
 void re2_options_set_longest_match(RE2Options* o, int longest) {
-    if (o) o->opts.set_longest_match(longest != 0);
+    guarded_void([&] {
+        if (o) o->opts.set_longest_match(longest != 0);
+    });
 }
 // ICU-related toggles
 void re2_options_set_word_boundary(RE2Options* o, int yes) {
-    if (o) o->opts.set_word_boundary(yes != 0);
+    guarded_void([&] {
+        if (o) o->opts.set_word_boundary(yes != 0);
+    });
 }
 void re2_options_set_perl_classes(RE2Options* o, int yes) {
-    if (o) o->opts.set_perl_classes(yes != 0);
+    guarded_void([&] {
+        if (o) o->opts.set_perl_classes(yes != 0);
+    });
+}
+void re2_options_set_log_errors(RE2Options* o, int yes) {
+    guarded_void([&] {
+        if (o) o->opts.set_log_errors(yes != 0);
+    });
+}
+void re2_options_set_max_mem(RE2Options* o, int64_t max_mem) {
+    guarded_void([&] {
+        if (o) o->opts.set_max_mem(max_mem);
+    });
+}
+void re2_options_set_engine(RE2Options* o, int engine) {
+    guarded_void([&] {
+        if (o) o->icu = engine == RE2_ENGINE_ICU;
+    });
+}
+void re2_options_set_unicode_perl_classes(RE2Options* o, int enabled) {
+    guarded_void([&] {
+        if (o) o->unicode_perl_classes = enabled != 0;
+    });
+}
+int64_t re2_options_max_mem(const RE2Options* o) {
+    return guarded<int64_t>(0, [&]() -> int64_t {
+        return o ? o->opts.max_mem() : re2::RE2::Options().max_mem();
+    });
+}
+RE2Options* re2_options_clone(const RE2Options* o) {
+    return guarded<RE2Options*>(nullptr, [&]() -> RE2Options* {
+        auto* copy = new RE2Options();
+        if (o) *copy = *o;
+        return copy;
+    });
 }
 
 
 RE2Wrapper* re2_new(const char* pattern, size_t pattern_len, const char** err_ptr, size_t* err_len) {
-    re2::StringPiece pat(pattern, pattern_len);
-    auto* w = new (std::nothrow) RE2Wrapper(pat);
-    if (!w) {
-        static const char kOOM[] = "re2_new: out of memory";
-        if (err_ptr) *err_ptr = kOOM;
-        if (err_len) *err_len = sizeof(kOOM) - 1;
-        return nullptr;
-    }
-    if (!w->re.ok()) {
-        if (err_ptr && err_len) {
-            const std::string& e = w->re.error();
-            *err_ptr = e.c_str();
-            *err_len = e.size();
+    return guarded<RE2Wrapper*>(nullptr, [&]() -> RE2Wrapper* {
+        re2::StringPiece pat(pattern, pattern_len);
+        auto* w = new RE2Wrapper(pat, pat, re2::RE2::Options());
+        if (!w->re.ok()) {
+            if (err_ptr && err_len) {
+                const std::string& e = w->re.error();
+                *err_ptr = e.c_str();
+                *err_len = e.size();
+            }
+        } else {
+            if (err_ptr) *err_ptr = nullptr;
+            if (err_len) *err_len = 0;
         }
-    } else {
-        if (err_ptr) *err_ptr = nullptr;
-        if (err_len) *err_len = 0;
-    }
-    return w;
+        return w;
+    });
 }
 
 // ----- construct with options -----
 RE2Wrapper* re2_new_with_options(const char* pattern, size_t pattern_len,
                                  const RE2Options* opts,
                                  const char** err_ptr, size_t* err_len) {
-    re2::StringPiece pat(pattern, pattern_len);
-    re2::RE2::Options o = opts ? opts->opts : re2::RE2::Options();
-    auto* w = new (std::nothrow) RE2Wrapper(pat, o);
-    if (!w) {
-        static const char kOOM[] = "re2_new_with_options: out of memory";
-        if (err_ptr) *err_ptr = kOOM;
-        if (err_len) *err_len = sizeof(kOOM) - 1;
-        return nullptr;
-    }
-    if (!w->re.ok()) {
-        if (err_ptr && err_len) {
-            const std::string& e = w->re.error();
-            *err_ptr = e.c_str();
-            *err_len = e.size();
+    return guarded<RE2Wrapper*>(nullptr, [&]() -> RE2Wrapper* {
+        re2::StringPiece written(pattern, pattern_len);
+        re2::StringPiece pat = written;
+        re2::RE2::Options o = opts ? opts->opts : re2::RE2::Options();
+        bool icu = opts && opts->icu;
+        bool unicode_classes = opts && opts->unicode_perl_classes;
+        bool rewrite = rewrites_perl_classes(unicode_classes, o);
+        std::string translated, error;
+        int code = re2::RE2::NoError;
+        std::unique_ptr<RE2Wrapper> w;
+        if (engine_pattern(icu, unicode_classes, o, &pat, &translated, &error, &code)) {
+            w.reset(new RE2Wrapper(written, pat, compile_options(o, icu, rewrite)));
+            if (rewrite && !w->re.ok()) {
+                error = w->re.error();
+                code = w->re.error_code();
+                written_error(icu, o, written, &error, &code);
+                w.reset(new RE2Wrapper(written, std::move(error), code));
+            }
+        } else {
+            w.reset(new RE2Wrapper(written, std::move(error), code));
         }
-    } else {
-        if (err_ptr) *err_ptr = nullptr;
-        if (err_len) *err_len = 0;
-    }
-    return w;
+        if (!w->engine_error.empty()) log_written_error(o, written, w->engine_error);
+        if (w->ok() && pat.data() != written.data()) {
+            register_written(&w->re, written);
+            w->registered = true;
+        }
+        if (!w->ok()) {
+            if (err_ptr && err_len) {
+                const std::string& e = w->error();
+                *err_ptr = e.c_str();
+                *err_len = e.size();
+            }
+        } else {
+            if (err_ptr) *err_ptr = nullptr;
+            if (err_len) *err_len = 0;
+        }
+        return w.release();
+    });
 }
 
+void re2_pattern(const RE2Wrapper* re2, const char** pattern_ptr, size_t* pattern_len) {
+    guarded_void([&] {
+        if (!pattern_ptr || !pattern_len) return;
+        *pattern_ptr = re2 ? re2->pattern.data() : nullptr;
+        *pattern_len = re2 ? re2->pattern.size() : 0;
+    });
+}
 
-void re2_delete(RE2Wrapper* re2) { delete re2; }
+void re2_delete(RE2Wrapper* re2) { guarded_void([&] { delete re2; }); }
 
-int re2_ok(const RE2Wrapper* re2) { return (re2 && re2->re.ok()) ? 1 : 0; }
+int re2_ok(const RE2Wrapper* re2) {
+    return guarded<int>(0, [&]() -> int { return (re2 && re2->ok()) ? 1 : 0; });
+}
 
 void re2_error(const RE2Wrapper* re2, const char** err_ptr, size_t* err_len) {
-    if (!re2 || !err_ptr || !err_len) return;
-    const std::string& e = re2->re.error();
-    *err_ptr = e.empty() ? nullptr : e.c_str();
-    *err_len = e.size();
+    guarded_void([&] {
+        if (!re2 || !err_ptr || !err_len) return;
+        const std::string& e = re2->error();
+        *err_ptr = e.empty() ? nullptr : e.c_str();
+        *err_len = e.size();
+    });
 }
 
 int re2_full_match(const RE2Wrapper* re2, const char* text, size_t text_len) {
-    if (!re2) return 0;
-    re2::StringPiece t(text, text_len);
-    return re2::RE2::FullMatch(t, re2->re) ? 1 : 0;
+    return guarded<int>(0, [&]() -> int {
+        if (!re2) return 0;
+        re2::StringPiece t(text, text_len);
+        return re2::RE2::FullMatch(t, re2->re) ? 1 : 0;
+    });
 }
 
 static int do_match_with_captures(
@@ -128,7 +781,7 @@ static int do_match_with_captures(
     re2::StringPiece t(text, text_len);
     bool ok = w->re.Match(
         t,
-        0, static_cast<int>(t.size()),
+        0, t.size(),
         anchor,
         subs.data(), ncap
     );
@@ -152,9 +805,11 @@ static int do_match_with_captures(
 }
 
 int re2_partial_match(const RE2Wrapper* re2, const char* text, size_t text_len) {
-    if (!re2) return 0;
-    re2::StringPiece t(text, text_len);
-    return re2->re.Match(t, 0, static_cast<int>(t.size()), re2::RE2::UNANCHORED, nullptr, 0) ? 1 : 0;
+    return guarded<int>(0, [&]() -> int {
+        if (!re2) return 0;
+        re2::StringPiece t(text, text_len);
+        return re2->re.Match(t, 0, t.size(), re2::RE2::UNANCHORED, nullptr, 0) ? 1 : 0;
+    });
 }
 
 int re2_partial_match_captures(
@@ -163,7 +818,9 @@ int re2_partial_match_captures(
     re2_span_t* out_spans, size_t out_spans_len,
     size_t* written
 ) {
-    return do_match_with_captures(re2, text, text_len, re2::RE2::UNANCHORED, out_spans, out_spans_len, written);
+    return guarded<int>(0, [&]() -> int {
+        return do_match_with_captures(re2, text, text_len, re2::RE2::UNANCHORED, out_spans, out_spans_len, written);
+    });
 }
 
 int re2_full_match_captures(
@@ -172,12 +829,60 @@ int re2_full_match_captures(
     re2_span_t* out_spans, size_t out_spans_len,
     size_t* written
 ) {
-    return do_match_with_captures(re2, text, text_len, re2::RE2::ANCHOR_BOTH, out_spans, out_spans_len, written);
+    return guarded<int>(0, [&]() -> int {
+        return do_match_with_captures(re2, text, text_len, re2::RE2::ANCHOR_BOTH, out_spans, out_spans_len, written);
+    });
 }
 
 int re2_group_count(const RE2Wrapper* re2) {
-    if (!re2) return 0;
-    return re2->re.NumberOfCapturingGroups();
+    return guarded<int>(0, [&]() -> int {
+        if (!re2) return 0;
+        return re2->re.NumberOfCapturingGroups();
+    });
+}
+
+int re2_error_code(const RE2Wrapper* re2) {
+    return guarded<int>(re2::RE2::ErrorInternal, [&]() -> int {
+        if (!re2) return re2::RE2::ErrorInternal;
+        if (!re2->engine_error.empty()) return re2->engine_error_code;
+        return re2->re.error_code();
+    });
+}
+
+int re2_program_size(const RE2Wrapper* re2) {
+    return guarded<int>(-1, [&]() -> int {
+        if (!re2) return -1;
+        return re2->re.ProgramSize();
+    });
+}
+
+int re2_max_repeat(const RE2Wrapper* re2) {
+    return guarded<int>(0, [&]() -> int {
+        if (!re2 || !re2->re.ok()) return 0;
+#ifdef RE2_RS_SYSTEM_RE2
+        needs_vendored_re2("the repeat count");
+#else
+        return static_cast<int>(max_repeat_count(re2->re.Regexp(), 1));
+#endif
+    });
+}
+
+size_t re2_approx_heap_bytes(const RE2Wrapper* re2) {
+    return guarded<size_t>(0, [&]() -> size_t {
+        if (!re2) return 0;
+        return base_bytes(re2) + program_bytes(re2->re);
+    });
+}
+
+size_t re2_max_heap_bytes(const RE2Wrapper* re2) {
+    return guarded<size_t>(0, [&]() -> size_t {
+        if (!re2) return 0;
+        // RE2 compiles the forward program within 2/3 of max_mem and the reverse one
+        // within the rest, and each program's DFAs get what its share leaves over.
+        int64_t max_mem = re2->re.options().max_mem();
+        size_t budget = base_bytes(re2) + static_cast<size_t>(std::max<int64_t>(max_mem, 0));
+        return std::max(budget, base_bytes(re2) + program_bytes(re2->re));
+    });
 }
 
 static int copy_out(const std::string& s, char* out_buf, size_t out_len, size_t* written) {
@@ -194,12 +899,14 @@ int re2_replace_one(const RE2Wrapper* w,
                     const char* rewrite, size_t rewrite_len,
                     char* out_buf, size_t out_len,
                     size_t* written) {
-    if (!w) return 0;
-    std::string result(text, text_len);
-    std::string rew(rewrite, rewrite_len);
-    bool ok = re2::RE2::Replace(&result, w->re, rew);
-    if (!ok) return 0;
-    return copy_out(result, out_buf, out_len, written);
+    return guarded<int>(0, [&]() -> int {
+        if (!w) return 0;
+        std::string result(text, text_len);
+        std::string rew(rewrite, rewrite_len);
+        bool ok = re2::RE2::Replace(&result, w->re, rew);
+        if (!ok) return 0;
+        return copy_out(result, out_buf, out_len, written);
+    });
 }
 
 int re2_replace_all(const RE2Wrapper* w,
@@ -207,22 +914,645 @@ int re2_replace_all(const RE2Wrapper* w,
                     const char* rewrite, size_t rewrite_len,
                     char* out_buf, size_t out_len,
                     size_t* written) {
-    if (!w) return 0;
-    std::string result(text, text_len);
-    std::string rew(rewrite, rewrite_len);
-    int n = re2::RE2::GlobalReplace(&result, w->re, rew);
-    if (n <= 0) return 0;
-    return copy_out(result, out_buf, out_len, written);
+    return guarded<int>(0, [&]() -> int {
+        if (!w) return 0;
+        std::string result(text, text_len);
+        std::string rew(rewrite, rewrite_len);
+        int n = re2::RE2::GlobalReplace(&result, w->re, rew);
+        if (n <= 0) return 0;
+        return copy_out(result, out_buf, out_len, written);
+    });
 }
 
 
-// Return 1 if this build of RE2 has ICU enabled, else 0.
+// Return 1 if the ICU engine is built in, else 0.
 int re2_has_icu() {
-#ifdef RE2_WITH_ICU
-    return 1;
+    return guarded<int>(0, [&]() -> int {
+    #ifdef RE2_RS_ICU_ENGINE
+        return 1;
+    #else
+        return 0;
+    #endif
+    });
+}
+
+// ----- RE2::Set -----
+RE2SetWrapper* re2_set_new(const RE2Options* opts, int anchor) {
+    return guarded<RE2SetWrapper*>(nullptr, [&]() -> RE2SetWrapper* {
+        re2::RE2::Options o = opts ? opts->opts : re2::RE2::Options();
+        return new RE2SetWrapper(o, opts && opts->icu, opts && opts->unicode_perl_classes, to_anchor(anchor));
+    });
+}
+
+void re2_set_delete(RE2SetWrapper* set) { guarded_void([&] { delete set; }); }
+
+int re2_set_add(RE2SetWrapper* set, const char* pattern, size_t pattern_len,
+                const char** err_ptr, size_t* err_len) {
+    return guarded<int>(-1, [&]() -> int {
+        if (err_ptr) *err_ptr = nullptr;
+        if (err_len) *err_len = 0;
+        if (!set) return -1;
+        set->last_error.clear();
+        re2::StringPiece written(pattern, pattern_len);
+        re2::StringPiece pat = written;
+        std::string translated;
+        int code = re2::RE2::NoError;
+        int idx = -1;
+        if (engine_pattern(set->icu, set->unicode_classes, set->opts, &pat, &translated, &set->last_error, &code)) {
+            idx = set->set.Add(pat, &set->last_error);
+            if (idx < 0 && rewrites_perl_classes(set->unicode_classes, set->opts)) {
+                written_error(set->icu, set->opts, written, &set->last_error, &code);
+                log_written_error(set->opts, written, set->last_error);
+            }
+        }
+        if (idx < 0 && err_ptr && err_len) {
+            *err_ptr = set->last_error.c_str();
+            *err_len = set->last_error.size();
+        }
+        return idx;
+    });
+}
+
+int re2_set_size(const RE2SetWrapper* set) {
+    return guarded<int>(0, [&]() -> int {
+        if (!set) return 0;
+        return set->set.Size();
+    });
+}
+
+int re2_set_compile(RE2SetWrapper* set) {
+    return guarded<int>(0, [&]() -> int {
+        if (!set) return 0;
+        return set->set.Compile() ? 1 : 0;
+    });
+}
+
+int re2_set_match(const RE2SetWrapper* set,
+                  const char* text, size_t text_len,
+                  int* out_indices, size_t out_len,
+                  size_t* written, int* error_kind) {
+    return guarded<int>(0, [&]() -> int {
+        if (written) *written = 0;
+        if (error_kind) *error_kind = re2::RE2::Set::kNoError;
+        if (!set) return 0;
+
+        re2::RE2::Set::ErrorInfo info;
+        info.kind = re2::RE2::Set::kNoError;
+        re2::StringPiece t(text, text_len);
+
+        bool ok;
+        if (out_indices) {
+            std::vector<int> v;
+            ok = set->set.Match(t, &v, &info);
+            size_t to_write = v.size() < out_len ? v.size() : out_len;
+            for (size_t i = 0; i < to_write; ++i) out_indices[i] = v[i];
+            if (written) *written = to_write;
+        } else {
+            ok = set->set.Match(t, nullptr, &info);
+        }
+        if (error_kind) *error_kind = static_cast<int>(info.kind);
+        return ok ? 1 : 0;
+    });
+}
+
+// ----- FilteredRE2 -----
+static size_t copy_ids(const std::vector<int>& ids, int* out_ids, size_t out_len) {
+    size_t n = ids.size() < out_len ? ids.size() : out_len;
+    for (size_t i = 0; i < n; ++i) out_ids[i] = ids[i];
+    return n;
+}
+
+RE2FilteredWrapper* re2_filtered_new(int min_atom_len) {
+    return guarded<RE2FilteredWrapper*>(nullptr, [&]() -> RE2FilteredWrapper* {
+        return new RE2FilteredWrapper(min_atom_len);
+    });
+}
+
+void re2_filtered_delete(RE2FilteredWrapper* f) { guarded_void([&] { delete f; }); }
+
+int re2_filtered_add(RE2FilteredWrapper* f,
+                     const char* pattern, size_t pattern_len,
+                     const RE2Options* opts,
+                     const char** err_ptr, size_t* err_len) {
+    return guarded<int>(-1, [&]() -> int {
+        if (err_ptr) *err_ptr = nullptr;
+        if (err_len) *err_len = 0;
+        if (!f) return -1;
+        re2::StringPiece written(pattern, pattern_len);
+        re2::StringPiece pat = written;
+        re2::RE2::Options o = opts ? opts->opts : re2::RE2::Options();
+        bool icu = opts && opts->icu;
+        bool unicode_classes = opts && opts->unicode_perl_classes;
+        bool rewrite = rewrites_perl_classes(unicode_classes, o);
+        std::string translated;
+        int code = re2::RE2::NoError;
+        if (!engine_pattern(icu, unicode_classes, o, &pat, &translated, &f->last_error, &code)) {
+            if (err_ptr && err_len) {
+                *err_ptr = f->last_error.c_str();
+                *err_len = f->last_error.size();
+            }
+            return -1;
+        }
+        int id = -1;
+        if (f->filtered.Add(pat, compile_options(o, icu, rewrite), &id) == re2::RE2::NoError) {
+            if (pat.data() != written.data()) {
+                const re2::RE2* re = &f->filtered.GetRE2(id);
+                register_written(re, written);
+                f->registered.push_back(re);
+            }
+            return id;
+        }
+
+        // FilteredRE2 discards the failed RE2, so recompile quietly for the message.
+        re2::RE2 probe(pat, compile_options(o, icu, true));
+        f->last_error = probe.error();
+        if (rewrite) {
+            code = probe.error_code();
+            written_error(icu, o, written, &f->last_error, &code);
+            log_written_error(o, written, f->last_error);
+        }
+        if (err_ptr && err_len) {
+            *err_ptr = f->last_error.c_str();
+            *err_len = f->last_error.size();
+        }
+        return -1;
+    });
+}
+
+int re2_filtered_size(const RE2FilteredWrapper* f) {
+    return guarded<int>(0, [&]() -> int {
+        if (!f) return 0;
+        return f->filtered.NumRegexps();
+    });
+}
+
+void re2_filtered_compile(RE2FilteredWrapper* f) {
+    guarded_void([&] {
+        if (!f) return;
+        f->filtered.Compile(&f->atoms);
+    });
+}
+
+size_t re2_filtered_num_atoms(const RE2FilteredWrapper* f) {
+    return guarded<size_t>(0, [&]() -> size_t {
+        if (!f) return 0;
+        return f->atoms.size();
+    });
+}
+
+void re2_filtered_atom(const RE2FilteredWrapper* f, size_t i,
+                       const char** atom_ptr, size_t* atom_len) {
+    guarded_void([&] {
+        if (!atom_ptr || !atom_len) return;
+        if (!f || i >= f->atoms.size()) {
+            *atom_ptr = nullptr;
+            *atom_len = 0;
+            return;
+        }
+        *atom_ptr = f->atoms[i].data();
+        *atom_len = f->atoms[i].size();
+    });
+}
+
+int re2_filtered_slow_first_match(const RE2FilteredWrapper* f,
+                                  const char* text, size_t text_len) {
+    return guarded<int>(-1, [&]() -> int {
+        if (!f) return -1;
+        return f->filtered.SlowFirstMatch(re2::StringPiece(text, text_len));
+    });
+}
+
+int re2_filtered_first_match(const RE2FilteredWrapper* f,
+                             const char* text, size_t text_len,
+                             const int* atoms, size_t atoms_len) {
+    return guarded<int>(-1, [&]() -> int {
+        if (!f) return -1;
+        std::vector<int> a(atoms, atoms + atoms_len);
+        return f->filtered.FirstMatch(re2::StringPiece(text, text_len), a);
+    });
+}
+
+int re2_filtered_all_matches(const RE2FilteredWrapper* f,
+                             const char* text, size_t text_len,
+                             const int* atoms, size_t atoms_len,
+                             int* out_ids, size_t out_len, size_t* written) {
+    return guarded<int>(0, [&]() -> int {
+        if (written) *written = 0;
+        if (!f) return 0;
+        std::vector<int> a(atoms, atoms + atoms_len);
+        std::vector<int> ids;
+        bool ok = f->filtered.AllMatches(re2::StringPiece(text, text_len), a, &ids);
+        size_t n = out_ids ? copy_ids(ids, out_ids, out_len) : 0;
+        if (written) *written = n;
+        return ok ? 1 : 0;
+    });
+}
+
+void re2_filtered_all_potentials(const RE2FilteredWrapper* f,
+                                 const int* atoms, size_t atoms_len,
+                                 int* out_ids, size_t out_len, size_t* written) {
+    guarded_void([&] {
+        if (written) *written = 0;
+        if (!f) return;
+        std::vector<int> a(atoms, atoms + atoms_len);
+        std::vector<int> ids;
+        f->filtered.AllPotentials(a, &ids);
+        size_t n = out_ids ? copy_ids(ids, out_ids, out_len) : 0;
+        if (written) *written = n;
+    });
+}
+
+// ----- Prefilter -----
+#ifndef RE2_RS_SYSTEM_RE2
+RE2Prefilter* re2_prefilter_from_re2(const RE2Wrapper* re2) {
+    return guarded<RE2Prefilter*>(nullptr, [&]() -> RE2Prefilter* {
+        if (!re2 || !re2->re.ok()) return nullptr;
+        return reinterpret_cast<RE2Prefilter*>(re2::Prefilter::FromRE2(&re2->re));
+    });
+}
+
+void re2_prefilter_delete(RE2Prefilter* pf) { guarded_void([&] { delete as_prefilter(pf); }); }
+
+int re2_prefilter_op(const RE2Prefilter* pf) {
+    return guarded<int>(0, [&]() -> int {
+        if (!pf) return re2::Prefilter::ALL;
+        return static_cast<int>(as_prefilter(pf)->op());
+    });
+}
+
+void re2_prefilter_atom(const RE2Prefilter* pf, const char** atom_ptr, size_t* atom_len) {
+    guarded_void([&] {
+        if (!atom_ptr || !atom_len) return;
+        if (!pf || as_prefilter(pf)->op() != re2::Prefilter::ATOM) {
+            *atom_ptr = nullptr;
+            *atom_len = 0;
+            return;
+        }
+        const std::string& a = as_prefilter(pf)->atom();
+        *atom_ptr = a.data();
+        *atom_len = a.size();
+    });
+}
+
+size_t re2_prefilter_num_subs(const RE2Prefilter* pf) {
+    return guarded<size_t>(0, [&]() -> size_t { return prefilter_num_subs(pf); });
+}
+
+const RE2Prefilter* re2_prefilter_sub(const RE2Prefilter* pf, size_t i) {
+    return guarded<const RE2Prefilter*>(nullptr, [&]() -> const RE2Prefilter* {
+        if (i >= prefilter_num_subs(pf)) return nullptr;
+        return reinterpret_cast<const RE2Prefilter*>((*as_prefilter(pf)->subs())[i]);
+    });
+}
+#else
+// Without Prefilter::FromRE2 no handle exists, and a null one reads as ALL
+RE2Prefilter* re2_prefilter_from_re2(const RE2Wrapper*) {
+    return guarded<RE2Prefilter*>(nullptr, []() -> RE2Prefilter* { return nullptr; });
+}
+void re2_prefilter_delete(RE2Prefilter*) {}
+int re2_prefilter_op(const RE2Prefilter*) { return 0; }
+void re2_prefilter_atom(const RE2Prefilter*, const char** atom_ptr, size_t* atom_len) {
+    if (atom_ptr) *atom_ptr = nullptr;
+    if (atom_len) *atom_len = 0;
+}
+size_t re2_prefilter_num_subs(const RE2Prefilter*) { return 0; }
+const RE2Prefilter* re2_prefilter_sub(const RE2Prefilter*, size_t) { return nullptr; }
+#endif
+
+// ----- Owned strings -----
+const char* re2_string_data(const RE2String* s) {
+    return guarded<const char*>(nullptr, [&]() -> const char* { return s ? s->s.data() : nullptr; });
+}
+size_t re2_string_len(const RE2String* s) {
+    return guarded<size_t>(0, [&]() -> size_t { return s ? s->s.size() : 0; });
+}
+void re2_string_delete(RE2String* s) { guarded_void([&] { delete s; }); }
+
+// ----- Regexp (parsed AST) -----
+#ifndef RE2_RS_SYSTEM_RE2
+RE2Regexp* re2_regexp_parse(const char* pattern, size_t pattern_len, int flags, RE2String** err) {
+    return guarded<RE2Regexp*>(nullptr, [&]() -> RE2Regexp* {
+        return reinterpret_cast<RE2Regexp*>(regexp_parse(pattern, pattern_len, flags, err));
+    });
+}
+
+void re2_regexp_delete(RE2Regexp* re) {
+    guarded_void([&] {
+        if (re) as_regexp(re)->Decref();
+    });
+}
+
+int re2_regexp_op(const RE2Regexp* re) {
+    return guarded<int>(0, [&]() -> int { return regexp_op(re); });
+}
+
+int re2_regexp_flags(const RE2Regexp* re) {
+    return guarded<int>(0, [&]() -> int {
+        return re ? static_cast<int>(as_regexp(re)->parse_flags()) : 0;
+    });
+}
+
+size_t re2_regexp_nsub(const RE2Regexp* re) {
+    return guarded<size_t>(0, [&]() -> size_t { return regexp_nsub(re); });
+}
+
+const RE2Regexp* re2_regexp_sub(const RE2Regexp* re, size_t i) {
+    return guarded<const RE2Regexp*>(nullptr, [&]() -> const RE2Regexp* {
+        if (i >= regexp_nsub(re)) return nullptr;
+        return reinterpret_cast<const RE2Regexp*>(as_regexp(re)->sub()[i]);
+    });
+}
+
+int re2_regexp_min(const RE2Regexp* re) {
+    return guarded<int>(0, [&]() -> int {
+        if (regexp_op(re) != re2::kRegexpRepeat) return 0;
+        return as_regexp(re)->min();
+    });
+}
+
+int re2_regexp_max(const RE2Regexp* re) {
+    return guarded<int>(0, [&]() -> int {
+        if (regexp_op(re) != re2::kRegexpRepeat) return 0;
+        return as_regexp(re)->max();
+    });
+}
+
+int re2_regexp_cap(const RE2Regexp* re) {
+    return guarded<int>(0, [&]() -> int {
+        if (regexp_op(re) != re2::kRegexpCapture) return 0;
+        return as_regexp(re)->cap();
+    });
+}
+
+void re2_regexp_name(const RE2Regexp* re, const char** name_ptr, size_t* name_len) {
+    guarded_void([&] {
+        if (!name_ptr || !name_len) return;
+        *name_ptr = nullptr;
+        *name_len = 0;
+        if (regexp_op(re) != re2::kRegexpCapture) return;
+        const std::string* name = as_regexp(re)->name();
+        if (!name) return;
+        *name_ptr = name->data();
+        *name_len = name->size();
+    });
+}
+
+int32_t re2_regexp_rune(const RE2Regexp* re) {
+    return guarded<int32_t>(0, [&]() -> int32_t {
+        if (regexp_op(re) != re2::kRegexpLiteral) return 0;
+        return as_regexp(re)->rune();
+    });
+}
+
+size_t re2_regexp_runes(const RE2Regexp* re, const int32_t** runes) {
+    return guarded<size_t>(0, [&]() -> size_t {
+        if (runes) *runes = nullptr;
+        if (regexp_op(re) != re2::kRegexpLiteralString) return 0;
+        if (runes) *runes = as_regexp(re)->runes();
+        return static_cast<size_t>(as_regexp(re)->nrunes());
+    });
+}
+
+size_t re2_regexp_num_ranges(const RE2Regexp* re) {
+    return guarded<size_t>(0, [&]() -> size_t { return regexp_num_ranges(re); });
+}
+
+void re2_regexp_range(const RE2Regexp* re, size_t i, int32_t* lo, int32_t* hi) {
+    guarded_void([&] {
+        if (!lo || !hi) return;
+        *lo = 0;
+        *hi = -1;
+        if (i >= regexp_num_ranges(re)) return;
+        const re2::RuneRange& r = as_regexp(re)->cc()->begin()[i];
+        *lo = r.lo;
+        *hi = r.hi;
+    });
+}
+
+int re2_regexp_match_id(const RE2Regexp* re) {
+    return guarded<int>(0, [&]() -> int {
+        if (regexp_op(re) != re2::kRegexpHaveMatch) return 0;
+        return as_regexp(re)->match_id();
+    });
+}
+
+int re2_regexp_mimics_pcre(const RE2Regexp* re) {
+    return guarded<int>(0, [&]() -> int {
+        if (!re) return 0;
+        return as_regexp(re)->MimicsPCRE() ? 1 : 0;
+    });
+}
+
+RE2String* re2_regexp_canonical(const char* pattern, size_t pattern_len, int flags, RE2String** err) {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+        re2::Regexp* re = regexp_parse(pattern, pattern_len, flags, err);
+        if (!re) return nullptr;
+        std::string s = to_pattern_string(re);
+        re->Decref();
+        return new_string(std::move(s));
+    });
+}
+
+RE2String* re2_regexp_simplify(const char* pattern, size_t pattern_len, int flags, RE2String** err) {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+        re2::Regexp* re = regexp_parse(pattern, pattern_len, flags, err);
+        if (!re) return nullptr;
+        re2::Regexp* sre = re->Simplify();
+        re->Decref();
+        if (!sre) {
+            if (err) *err = new_string("internal error: simplify failed");
+            return nullptr;
+        }
+        RE2String* out = new_string(to_pattern_string(sre));
+        sre->Decref();
+        return out;
+    });
+}
+
+RE2String* re2_required_prefix(const RE2Wrapper* re2, int* foldcase) {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+        if (foldcase) *foldcase = 0;
+        if (!re2 || !re2->re.ok()) return nullptr;
+        re2::Regexp* re = re2->re.Regexp();
+        if (!re) return nullptr;
+
+        std::string prefix;
+        bool fold = false;
+        if (!re->RequiredPrefixForAccel(&prefix, &fold)) {
+            re2::Regexp* suffix = nullptr;
+            if (!re->RequiredPrefix(&prefix, &fold, &suffix)) return nullptr;
+            suffix->Decref();
+        }
+        if (prefix.empty()) return nullptr;
+        if (foldcase) *foldcase = fold ? 1 : 0;
+        return new_string(std::move(prefix));
+    });
+}
+
+RE2String* re2_prog_dump(const RE2Wrapper* re2) {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+        std::unique_ptr<re2::Prog> prog = compile_prog(re2);
+        return prog ? new_string(prog->Dump()) : nullptr;
+    });
+}
+
+RE2String* re2_prog_dump_unanchored(const RE2Wrapper* re2) {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+        std::unique_ptr<re2::Prog> prog = compile_prog(re2);
+        return prog ? new_string(prog->DumpUnanchored()) : nullptr;
+    });
+}
+
+RE2String* re2_prog_dump_byte_map(const RE2Wrapper* re2) {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+        std::unique_ptr<re2::Prog> prog = compile_prog(re2);
+        return prog ? new_string(prog->DumpByteMap()) : nullptr;
+    });
+}
+
+RE2String* re2_prog_dot(const RE2Wrapper* re2) {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+        std::unique_ptr<re2::Prog> prog = compile_prog(re2);
+        return prog ? new_string(prog_to_dot(prog.get())) : nullptr;
+    });
+}
 #else
+// Parsing fails, so no handle exists and the accessors only see null
+RE2Regexp* re2_regexp_parse(const char*, size_t, int, RE2String** err) {
+    return guarded<RE2Regexp*>(nullptr, [&]() -> RE2Regexp* {
+        if (err) *err = nullptr;
+        needs_vendored_re2("Regexp::Parse");
+    });
+}
+
+void re2_regexp_delete(RE2Regexp*) {}
+int re2_regexp_op(const RE2Regexp*) { return 0; }
+int re2_regexp_flags(const RE2Regexp*) { return 0; }
+size_t re2_regexp_nsub(const RE2Regexp*) { return 0; }
+const RE2Regexp* re2_regexp_sub(const RE2Regexp*, size_t) { return nullptr; }
+int re2_regexp_min(const RE2Regexp*) { return 0; }
+int re2_regexp_max(const RE2Regexp*) { return 0; }
+int re2_regexp_cap(const RE2Regexp*) { return 0; }
+void re2_regexp_name(const RE2Regexp*, const char** name_ptr, size_t* name_len) {
+    if (name_ptr) *name_ptr = nullptr;
+    if (name_len) *name_len = 0;
+}
+int32_t re2_regexp_rune(const RE2Regexp*) { return 0; }
+size_t re2_regexp_runes(const RE2Regexp*, const int32_t** runes) {
+    if (runes) *runes = nullptr;
     return 0;
+}
+size_t re2_regexp_num_ranges(const RE2Regexp*) { return 0; }
+void re2_regexp_range(const RE2Regexp*, size_t, int32_t* lo, int32_t* hi) {
+    if (lo) *lo = 0;
+    if (hi) *hi = -1;
+}
+int re2_regexp_match_id(const RE2Regexp*) { return 0; }
+int re2_regexp_mimics_pcre(const RE2Regexp*) { return 0; }
+
+RE2String* re2_regexp_canonical(const char*, size_t, int, RE2String** err) {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+        if (err) *err = nullptr;
+        needs_vendored_re2("Regexp::ToString");
+    });
+}
+
+RE2String* re2_regexp_simplify(const char*, size_t, int, RE2String** err) {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+        if (err) *err = nullptr;
+        needs_vendored_re2("Regexp::Simplify");
+    });
+}
+
+// No prefix and no program: the Rust side reads these as None and ""
+RE2String* re2_required_prefix(const RE2Wrapper*, int* foldcase) {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+        if (foldcase) *foldcase = 0;
+        return nullptr;
+    });
+}
+
+RE2String* re2_prog_dump(const RE2Wrapper*) { return nullptr; }
+RE2String* re2_prog_dump_unanchored(const RE2Wrapper*) { return nullptr; }
+RE2String* re2_prog_dump_byte_map(const RE2Wrapper*) { return nullptr; }
+RE2String* re2_prog_dot(const RE2Wrapper*) { return nullptr; }
 #endif
+
+void re2_set_dfa_state_cache_reset_hook(RE2DFAStateCacheResetCallback cb) {
+    guarded_void([&] {
+        dfa_state_cache_reset_cb.store(cb);
+        if (cb) re2::hooks::SetDFAStateCacheResetHook(&dfa_state_cache_reset_trampoline);
+    });
+}
+
+void re2_set_dfa_search_failure_hook(RE2DFASearchFailureCallback cb) {
+    guarded_void([&] {
+        dfa_search_failure_cb.store(cb);
+        if (cb) re2::hooks::SetDFASearchFailureHook(&dfa_search_failure_trampoline);
+    });
+}
+
+void re2_set_log_callback(RE2LogCallback cb) {
+    guarded_void([&] {
+        static std::once_flag once;
+        std::call_once(once, [] {
+            // Until Abseil logging is initialized, its stderr sink ignores the threshold
+            // and prints everything; keep doing that once it applies
+            if (!absl::log_internal::IsInitialized()) {
+                absl::InitializeLog();
+                absl::SetStderrThreshold(absl::LogSeverityAtLeast::kInfo);
+            }
+            absl::AddLogSink(new CallbackLogSink());
+        });
+        std::lock_guard<std::mutex> lock(log_cb_mu);
+        RE2LogCallback previous = log_cb.exchange(cb);
+        if (cb && !previous) saved_stderr_threshold = absl::StderrThreshold();
+        absl::SetStderrThreshold(cb ? absl::LogSeverityAtLeast::kInfinity : saved_stderr_threshold);
+    });
+}
+
+RE2String* re2_abseil_version() {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+    #ifdef ABSL_LTS_RELEASE_VERSION
+        return new_string(std::to_string(ABSL_LTS_RELEASE_VERSION) + "." +
+                          std::to_string(ABSL_LTS_RELEASE_PATCH_LEVEL));
+    #else
+        // Abseil built from its head rather than an LTS release
+        return new_string("head");
+    #endif
+    });
+}
+
+RE2String* re2_icu_version() {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+    #ifdef RE2_RS_ICU_ENGINE
+        std::string icu, unicode;
+        re2_rs::icu_versions(&icu, &unicode);
+        return new_string(icu);
+    #else
+        return nullptr;
+    #endif
+    });
+}
+
+RE2String* re2_icu_unicode_version() {
+    return guarded<RE2String*>(nullptr, [&]() -> RE2String* {
+    #ifdef RE2_RS_ICU_ENGINE
+        std::string icu, unicode;
+        re2_rs::icu_versions(&icu, &unicode);
+        return new_string(unicode);
+    #else
+        return nullptr;
+    #endif
+    });
+}
+
+int re2_last_status(void) { return last_status; }
+
+void re2_last_status_message(const char** msg_ptr, size_t* msg_len) {
+    if (!msg_ptr || !msg_len) return;
+    *msg_ptr = last_status_message.data();
+    *msg_len = last_status_message.size();
 }
 
 } // extern "C"
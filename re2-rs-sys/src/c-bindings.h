@@ -9,6 +9,16 @@ extern "C" {
 
 typedef struct RE2Wrapper RE2Wrapper;
 
+// Status of the last call into the shim on the calling thread. Every function catches
+// C++ exceptions, records them here and returns its usual failure value (NULL, 0 or -1).
+#define RE2_STATUS_OK            0
+#define RE2_STATUS_OUT_OF_MEMORY 1  // std::bad_alloc
+#define RE2_STATUS_INTERNAL      2  // any other exception
+
+int  re2_last_status(void);
+// what() of the exception behind the last non-OK status; valid until the next call on this thread
+void re2_last_status_message(const char** msg_ptr, size_t* msg_len);
+
 // NEW: byte-range span into the input text. start is byte offset.
 // If a group didn't participate, start == SIZE_MAX and len == 0.
 typedef struct {
@@ -29,6 +39,19 @@ void        re2_options_set_posix_syntax(RE2Options* o, int posix);
 void        re2_options_set_longest_match(RE2Options* o, int longest);
 void        re2_options_set_word_boundary(RE2Options* o, int yes);
 void        re2_options_set_perl_classes(RE2Options* o, int yes);
+void        re2_options_set_log_errors(RE2Options* o, int yes);
+void        re2_options_set_max_mem(RE2Options* o, int64_t max_mem);
+// Which build of RE2 parses patterns compiled with these options. The ICU engine resolves
+// \p{...} with ICU's property data; it exists only if the shim was built with ICU, and
+// patterns for it fail to compile otherwise (re2_has_icu reports which).
+#define RE2_ENGINE_PLAIN 0
+#define RE2_ENGINE_ICU   1
+void        re2_options_set_engine(RE2Options* o, int engine);
+// Rewrite \d \s \w (and \D \S \W) to Unicode tables before parsing. \b and \B have no
+// Unicode form, so patterns using them fail to compile (RE2::ErrorBadEscape).
+void        re2_options_set_unicode_perl_classes(RE2Options* o, int enabled);
+int64_t     re2_options_max_mem(const RE2Options* o);
+RE2Options* re2_options_clone(const RE2Options* o);
 
 // Construct with options
 RE2Wrapper* re2_new_with_options(const char* pattern, size_t pattern_len,
@@ -38,10 +61,15 @@ RE2Wrapper* re2_new_with_options(const char* pattern, size_t pattern_len,
 void        re2_delete(RE2Wrapper* re2);
 int         re2_ok(const RE2Wrapper* re2);
 void        re2_error(const RE2Wrapper* re2, const char** err_ptr, size_t* err_len);
+// The pattern as passed to re2_new*, before any Unicode Perl classes rewrite or ICU translation
+void        re2_pattern(const RE2Wrapper* re2, const char** pattern_ptr, size_t* pattern_len);
 int         re2_full_match(const RE2Wrapper* re2, const char* text, size_t text_len);
 
+// Texts and offsets are size_t throughout: inputs larger than INT_MAX bytes are fine.
 
-
+// Replace the first match. Returns 1 and copies the NUL-terminated result into `out_buf`
+// on success. Returns 0 if nothing matched, or if `out_len` is too small; in that case
+// `*written` is set to the length the result needs (excluding the NUL).
 int re2_replace_one(const RE2Wrapper* re,
                     const char* text, size_t text_len,
                     const char* rewrite, size_t rewrite_len,
@@ -78,9 +106,189 @@ int re2_full_match_captures(
 
 int re2_group_count(const RE2Wrapper* re2);
 
-// Return 1 if this build of RE2 has ICU enabled, else 0.
+// re2::RE2::ErrorCode of the regex (0 = NoError, 15 = ErrorPatternTooLarge)
+int re2_error_code(const RE2Wrapper* re2);
+// Instruction count of the compiled forward program (RE2::ProgramSize), -1 if invalid
+int re2_program_size(const RE2Wrapper* re2);
+// Largest count a {n,m} repetition can expand to: bounds of nested repeats multiply,
+// as in the parser's own limit check. 0 if the pattern has no counted repetition.
+int re2_max_repeat(const RE2Wrapper* re2);
+// Approximate heap footprint in bytes: the RE2 object, its pattern, the forward program,
+// the reverse program once matching has compiled it, and what the DFA caches hold now.
+// Builds nothing. Against a system RE2, only the object, pattern and forward program.
+// Must not be called for a regex from its own DFA state cache reset hook.
+size_t re2_approx_heap_bytes(const RE2Wrapper* re2);
+// Upper bound in bytes on the footprint, however much the regex is used: the RE2 object
+// and pattern plus max_mem, RE2's budget for the programs and their DFA caches.
+size_t re2_max_heap_bytes(const RE2Wrapper* re2);
+
+// Return 1 if the ICU engine is built in, else 0.
 int re2_has_icu();
 
+// ----- RE2::Set -----
+// Multi-pattern matcher. `anchor` mirrors re2::RE2::Anchor:
+// 0 = UNANCHORED, 1 = ANCHOR_START, 2 = ANCHOR_BOTH.
+typedef struct RE2SetWrapper RE2SetWrapper;
+
+// `opts` may be NULL for default options.
+RE2SetWrapper* re2_set_new(const RE2Options* opts, int anchor);
+void           re2_set_delete(RE2SetWrapper* set);
+
+// Returns the index of the added pattern, or -1 on parse error. The error message
+// is owned by the set and stays valid until the next call to re2_set_add.
+int re2_set_add(RE2SetWrapper* set, const char* pattern, size_t pattern_len,
+                const char** err_ptr, size_t* err_len);
+int re2_set_size(const RE2SetWrapper* set);
+
+// Returns 1 on success, 0 if the compiler ran out of memory.
+int re2_set_compile(RE2SetWrapper* set);
+
+// Returns 1 if at least one pattern matched. Matching indices are written to
+// `out_indices` (pass NULL to only test for a match). `*error_kind` receives
+// re2::RE2::Set::ErrorKind: 0 = none, 1 = not compiled, 2 = out of memory,
+// 3 = inconsistent.
+int re2_set_match(const RE2SetWrapper* set,
+                  const char* text, size_t text_len,
+                  int* out_indices, size_t out_len,
+                  size_t* written, int* error_kind);
+
+// ----- FilteredRE2 -----
+// Prefilter engine: patterns are reduced to literal "atoms" which the caller
+// searches for, then only patterns whose atoms occurred are run.
+typedef struct RE2FilteredWrapper RE2FilteredWrapper;
+
+RE2FilteredWrapper* re2_filtered_new(int min_atom_len);
+void                re2_filtered_delete(RE2FilteredWrapper* f);
+
+// Returns the id of the added pattern, or -1 if it failed to compile. The error
+// message is owned by `f` and stays valid until the next call to re2_filtered_add.
+int re2_filtered_add(RE2FilteredWrapper* f,
+                     const char* pattern, size_t pattern_len,
+                     const RE2Options* opts,
+                     const char** err_ptr, size_t* err_len);
+int re2_filtered_size(const RE2FilteredWrapper* f);
+
+// Computes the atoms; read them back with re2_filtered_num_atoms/re2_filtered_atom.
+void   re2_filtered_compile(RE2FilteredWrapper* f);
+size_t re2_filtered_num_atoms(const RE2FilteredWrapper* f);
+void   re2_filtered_atom(const RE2FilteredWrapper* f, size_t i,
+                         const char** atom_ptr, size_t* atom_len);
+
+// Matching. `atoms` are indices into the compiled atom list that occur in the text.
+// Match functions return the pattern id, or -1 on no match.
+int re2_filtered_slow_first_match(const RE2FilteredWrapper* f,
+                                  const char* text, size_t text_len);
+int re2_filtered_first_match(const RE2FilteredWrapper* f,
+                             const char* text, size_t text_len,
+                             const int* atoms, size_t atoms_len);
+int re2_filtered_all_matches(const RE2FilteredWrapper* f,
+                             const char* text, size_t text_len,
+                             const int* atoms, size_t atoms_len,
+                             int* out_ids, size_t out_len, size_t* written);
+void re2_filtered_all_potentials(const RE2FilteredWrapper* f,
+                                 const int* atoms, size_t atoms_len,
+                                 int* out_ids, size_t out_len, size_t* written);
+
+// ----- Prefilter -----
+// The AND/OR literal query implied by a regex (re2::Prefilter::FromRE2).
+// Nodes returned by re2_prefilter_sub are owned by the root.
+typedef struct RE2Prefilter RE2Prefilter;
+
+// Returns NULL when no prefilter could be computed (treat as "match everything").
+RE2Prefilter*       re2_prefilter_from_re2(const RE2Wrapper* re2);
+void                re2_prefilter_delete(RE2Prefilter* pf);
+// 0 = ALL, 1 = NONE, 2 = ATOM, 3 = AND, 4 = OR
+int                 re2_prefilter_op(const RE2Prefilter* pf);
+void                re2_prefilter_atom(const RE2Prefilter* pf, const char** atom_ptr, size_t* atom_len);
+size_t              re2_prefilter_num_subs(const RE2Prefilter* pf);
+const RE2Prefilter* re2_prefilter_sub(const RE2Prefilter* pf, size_t i);
+
+// ----- Owned strings -----
+// Heap string returned by the shim; the caller must free it with re2_string_delete.
+typedef struct RE2String RE2String;
+
+const char* re2_string_data(const RE2String* s);
+size_t      re2_string_len(const RE2String* s);
+void        re2_string_delete(RE2String* s);
+
+// ----- Regexp (parsed AST) -----
+// `flags` are re2::Regexp::ParseFlags. Nodes returned by re2_regexp_sub are owned by the root.
+typedef struct RE2Regexp RE2Regexp;
+
+// Returns NULL on error and sets `*err` (if non-NULL) to an owned error message.
+RE2Regexp*       re2_regexp_parse(const char* pattern, size_t pattern_len, int flags, RE2String** err);
+void             re2_regexp_delete(RE2Regexp* re);
+// re2::RegexpOp value (kRegexpNoMatch = 1 ... kRegexpHaveMatch = 21)
+int              re2_regexp_op(const RE2Regexp* re);
+int              re2_regexp_flags(const RE2Regexp* re);
+size_t           re2_regexp_nsub(const RE2Regexp* re);
+const RE2Regexp* re2_regexp_sub(const RE2Regexp* re, size_t i);
+// Repeat bounds; max is -1 when unbounded
+int              re2_regexp_min(const RE2Regexp* re);
+int              re2_regexp_max(const RE2Regexp* re);
+// Capture index and name (name is NULL for unnamed groups)
+int              re2_regexp_cap(const RE2Regexp* re);
+void             re2_regexp_name(const RE2Regexp* re, const char** name_ptr, size_t* name_len);
+// Literal / LiteralString runes
+int32_t          re2_regexp_rune(const RE2Regexp* re);
+size_t           re2_regexp_runes(const RE2Regexp* re, const int32_t** runes);
+// CharClass ranges, inclusive
+size_t           re2_regexp_num_ranges(const RE2Regexp* re);
+void             re2_regexp_range(const RE2Regexp* re, size_t i, int32_t* lo, int32_t* hi);
+int              re2_regexp_match_id(const RE2Regexp* re);
+// 1 if RE2 matches exactly as PCRE would for this tree (Regexp::MimicsPCRE), 0 otherwise
+int              re2_regexp_mimics_pcre(const RE2Regexp* re);
+
+// Canonical form of a pattern (Regexp::ToString of the parse tree, wrapped in (?s:...)
+// when it contains AnyChar so that it parses back the same). Returns an owned
+// string, or NULL on error with `*err` (if non-NULL) set to an owned error message.
+RE2String* re2_regexp_canonical(const char* pattern, size_t pattern_len, int flags, RE2String** err);
+// As above, after Regexp::Simplify (counted repeats expanded, classes normalised).
+RE2String* re2_regexp_simplify(const char* pattern, size_t pattern_len, int flags, RE2String** err);
+
+// Literal prefix every match must start with (Regexp::RequiredPrefixForAccel, falling back
+// to Regexp::RequiredPrefix for ^-anchored patterns). Returns an owned string, or NULL if
+// there is none. `*foldcase` is set to 1 if the prefix matches ASCII case-insensitively.
+RE2String* re2_required_prefix(const RE2Wrapper* re2, int* foldcase);
+
+// ----- Compiled program (debugging) -----
+// Each call compiles the forward re2::Prog the regex matches with and returns an
+// owned string, or NULL if the regex is invalid or the program exceeds max_mem.
+RE2String* re2_prog_dump(const RE2Wrapper* re2);
+RE2String* re2_prog_dump_unanchored(const RE2Wrapper* re2);
+RE2String* re2_prog_dump_byte_map(const RE2Wrapper* re2);
+// Graphviz DOT of the instruction graph
+RE2String* re2_prog_dot(const RE2Wrapper* re2);
+
+// ----- Hooks -----
+// Process-wide callbacks for re2::hooks. `pattern` is the pattern of the RE2 being
+// matched on the calling thread, or NULL when unknown (e.g. inside RE2::Set).
+// Callbacks run with the DFA cache locked and must not use RE2 for matching.
+// Pass NULL to stop receiving events.
+typedef void (*RE2DFAStateCacheResetCallback)(int64_t state_budget, size_t state_cache_size,
+                                              const char* pattern, size_t pattern_len);
+typedef void (*RE2DFASearchFailureCallback)(const char* pattern, size_t pattern_len);
+
+void re2_set_dfa_state_cache_reset_hook(RE2DFAStateCacheResetCallback cb);
+void re2_set_dfa_search_failure_hook(RE2DFASearchFailureCallback cb);
+
+// ----- Logging -----
+// Process-wide callback for RE2's (Abseil) log messages, replacing the default
+// stderr output. `severity` is absl::LogSeverity (0 info, 1 warning, 2 error, 3 fatal).
+// Pass NULL to go back to stderr, at the threshold in effect before the callback was set.
+typedef void (*RE2LogCallback)(int severity, const char* file, size_t file_len, int line,
+                               const char* msg, size_t msg_len);
+
+void re2_set_log_callback(RE2LogCallback cb);
+
+// ----- Build info -----
+// Abseil LTS release the shim was compiled against, e.g. "20250512.1".
+RE2String* re2_abseil_version();
+// ICU library and Unicode data versions linked at runtime, e.g. "77.1" and "16.0".
+// NULL without the ICU engine.
+RE2String* re2_icu_version();
+RE2String* re2_icu_unicode_version();
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
@@ -0,0 +1,81 @@
+// src/footprint.cc
+// The shim's memory accounting. RE2 keeps its reverse program and its DFAs private, and
+// the DFA class exists only in dfa.cc, so build.rs compiles vendor/re2/dfa.cc as part of
+// this file instead of on its own. Explicit instantiation may name private members,
+// which lets us read the few we need without patching RE2.
+#include "dfa.cc"
+#include "footprint.h"
+#include <absl/base/call_once.h>
+#include <absl/synchronization/mutex.h>
+
+namespace re2_rs {
+
+template <typename Tag, typename Tag::type M>
+struct PrivateMember {
+    friend typename Tag::type get(Tag) { return M; }
+};
+
+struct RE2Prog { using type = re2::Prog* re2::RE2::*; friend type get(RE2Prog); };
+struct RE2ReverseProg { using type = re2::Prog* re2::RE2::*; friend type get(RE2ReverseProg); };
+struct RE2ReverseProgOnce { using type = absl::once_flag re2::RE2::*; friend type get(RE2ReverseProgOnce); };
+struct ProgDFAFirst { using type = re2::DFA* re2::Prog::*; friend type get(ProgDFAFirst); };
+struct ProgDFALongest { using type = re2::DFA* re2::Prog::*; friend type get(ProgDFALongest); };
+struct ProgDFAFirstOnce { using type = absl::once_flag re2::Prog::*; friend type get(ProgDFAFirstOnce); };
+struct ProgDFALongestOnce { using type = absl::once_flag re2::Prog::*; friend type get(ProgDFALongestOnce); };
+struct DFAKind { using type = re2::Prog::MatchKind re2::DFA::*; friend type get(DFAKind); };
+struct DFAInitFailed { using type = bool re2::DFA::*; friend type get(DFAInitFailed); };
+struct DFAMutex { using type = absl::Mutex re2::DFA::*; friend type get(DFAMutex); };
+struct DFACacheMutex { using type = absl::Mutex re2::DFA::*; friend type get(DFACacheMutex); };
+struct DFAMemBudget { using type = int64_t re2::DFA::*; friend type get(DFAMemBudget); };
+
+template struct PrivateMember<RE2Prog, &re2::RE2::prog_>;
+template struct PrivateMember<RE2ReverseProg, &re2::RE2::rprog_>;
+template struct PrivateMember<RE2ReverseProgOnce, &re2::RE2::rprog_once_>;
+template struct PrivateMember<ProgDFAFirst, &re2::Prog::dfa_first_>;
+template struct PrivateMember<ProgDFALongest, &re2::Prog::dfa_longest_>;
+template struct PrivateMember<ProgDFAFirstOnce, &re2::Prog::dfa_first_once_>;
+template struct PrivateMember<ProgDFALongestOnce, &re2::Prog::dfa_longest_once_>;
+template struct PrivateMember<DFAKind, &re2::DFA::kind_>;
+template struct PrivateMember<DFAInitFailed, &re2::DFA::init_failed_>;
+template struct PrivateMember<DFAMutex, &re2::DFA::mutex_>;
+template struct PrivateMember<DFACacheMutex, &re2::DFA::cache_mutex_>;
+template struct PrivateMember<DFAMemBudget, &re2::DFA::mem_budget_>;
+
+// RE2 sets a lazily built program or DFA inside absl::call_once, whose completion is
+// published with release ordering, so the pointer may be read once the flag is done.
+static bool built(const absl::once_flag& flag) {
+    auto* control = absl::base_internal::ControlWord(const_cast<absl::once_flag*>(&flag));
+    return control->load(std::memory_order_acquire) == absl::base_internal::kOnceDone;
+}
+
+// Memory `dfa` has charged against the budget Prog::GetDFA gave it
+static size_t dfa_bytes(re2::Prog* prog, re2::DFA* dfa) {
+    if (!dfa) return 0;
+    if (dfa->*get(DFAInitFailed())) return sizeof(re2::DFA);
+    re2::Prog::MatchKind kind = dfa->*get(DFAKind());
+    bool whole = kind == re2::Prog::kManyMatch || (kind == re2::Prog::kLongestMatch && prog->reversed());
+    int64_t budget = whole ? prog->dfa_mem() : prog->dfa_mem() / 2;
+    // Searches add states holding cache_mutex_ for reading and mutex_, and resets
+    // refill the budget holding cache_mutex_ for writing
+    absl::ReaderMutexLock cache_lock(&(dfa->*get(DFACacheMutex())));
+    absl::MutexLock lock(&(dfa->*get(DFAMutex())));
+    int64_t left = std::max<int64_t>(dfa->*get(DFAMemBudget()), 0);
+    return static_cast<size_t>(std::max<int64_t>(budget - left, 0));
+}
+
+// RE2's own estimate of a program's size (see Compiler::Finish), plus its DFAs
+static size_t prog_bytes(re2::Prog* prog) {
+    if (!prog) return 0;
+    size_t bytes = sizeof(re2::Prog) + static_cast<size_t>(prog->size()) * sizeof(re2::Prog::Inst);
+    if (built(prog->*get(ProgDFAFirstOnce()))) bytes += dfa_bytes(prog, prog->*get(ProgDFAFirst()));
+    if (built(prog->*get(ProgDFALongestOnce()))) bytes += dfa_bytes(prog, prog->*get(ProgDFALongest()));
+    return bytes;
+}
+
+size_t program_bytes(const re2::RE2& re) {
+    size_t bytes = prog_bytes(re.*get(RE2Prog()));
+    if (built(re.*get(RE2ReverseProgOnce()))) bytes += prog_bytes(re.*get(RE2ReverseProg()));
+    return bytes;
+}
+
+}  // namespace re2_rs
@@ -0,0 +1,16 @@
+// src/footprint.h
+// Internal interface between the shim and its memory accounting. Not part of the C API.
+#pragma once
+#include <stddef.h>
+#include <re2/re2.h>
+
+namespace re2_rs {
+
+// Bytes held by the compiled programs of `re` and by the DFAs built for them so far:
+// the forward program, the reverse program once RE2 has compiled it, and the memory
+// each DFA has charged against its budget (the DFA itself and its cached states).
+// Programs and DFAs that do not exist yet are not built. Takes each DFA's cache locks,
+// so it must not be called for an RE2 from inside its own DFA state cache reset hook.
+size_t program_bytes(const re2::RE2& re);
+
+}  // namespace re2_rs
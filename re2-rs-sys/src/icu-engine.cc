@@ -0,0 +1,48 @@
+// src/icu-engine.cc
+// The ICU engine. build.rs compiles this file together with a second copy of RE2, built
+// with RE2_USE_ICU and `re2` defined to `re2_icu` so that none of its symbols clash with
+// the plain copy. ICU only changes how the parser looks up Unicode properties, so this
+// copy only parses; the plain engine compiles and matches the result.
+#include "icu-engine.h"
+#include <re2/regexp.h>
+#include <unicode/uchar.h>
+#include <unicode/uversion.h>
+
+namespace re2_rs {
+
+static bool contains_any_char(re2::Regexp* re) {
+    if (re->op() == re2::kRegexpAnyChar) return true;
+    for (int i = 0; i < re->nsub(); ++i)
+        if (contains_any_char(re->sub()[i])) return true;
+    return false;
+}
+
+bool icu_translate(const char* pattern, size_t pattern_len, int parse_flags,
+                   std::string* out, std::string* error, int* code) {
+    re2::RegexpStatus status;
+    re2::Regexp* re = re2::Regexp::Parse(absl::string_view(pattern, pattern_len),
+                                         static_cast<re2::Regexp::ParseFlags>(parse_flags), &status);
+    if (!re) {
+        *error = status.Text();
+        *code = status.code();
+        return false;
+    }
+    // Regexp::ToString writes ^ and $ for the multi-line forms (the others are (?-m:^),
+    // (?-m:$) and \z) and a bare . for AnyChar, so spell out the flags it assumes.
+    *out = (contains_any_char(re) ? "(?ms:" : "(?m:") + re->ToString() + ")";
+    re->Decref();
+    return true;
+}
+
+void icu_versions(std::string* icu, std::string* unicode) {
+    UVersionInfo version;
+    char text[U_MAX_VERSION_STRING_LENGTH];
+    u_getVersion(version);
+    u_versionToString(version, text);
+    *icu = text;
+    u_getUnicodeVersion(version);
+    u_versionToString(version, text);
+    *unicode = text;
+}
+
+}  // namespace re2_rs
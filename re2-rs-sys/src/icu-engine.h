@@ -0,0 +1,19 @@
+// src/icu-engine.h
+// Internal interface between the shim and the ICU engine. Not part of the C API.
+#pragma once
+#include <stddef.h>
+#include <string>
+
+namespace re2_rs {
+
+// Parse `pattern` with the ICU build of RE2, which resolves \p{...} with ICU's property
+// data, and print the tree back as a pattern that the plain build parses to the same
+// tree under Perl syntax without case folding. On a parse error returns false and sets
+// *error and *code (a re2::RegexpStatusCode, numbered as RE2::ErrorCode).
+bool icu_translate(const char* pattern, size_t pattern_len, int parse_flags,
+                   std::string* out, std::string* error, int* code);
+
+// Versions of the linked ICU library and of its Unicode data, e.g. "77.1" and "16.0".
+void icu_versions(std::string* icu, std::string* unicode);
+
+}  // namespace re2_rs
@@ -1,7 +1,5 @@
 #![allow(non_camel_case_types, non_snake_case, non_upper_case_globals)]
 
-mod bindings;
-
 #[allow(dead_code)]
 #[cfg(feature = "bindgen")]
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
@@ -13,4 +11,30 @@ mod prebuilt {
     include!("bindings.rs");
 }
 #[cfg(not(feature = "bindgen"))]
-pub use prebuilt::*;
\ No newline at end of file
+pub use prebuilt::*;
+
+/// Build facts recorded by build.rs
+pub mod build_info {
+    /// RE2 release: the vendored one, or `system <re2.pc version>` with `system-re2`
+    pub const RE2_VERSION: &str = env!("RE2_RS_RE2_VERSION");
+    /// Whether the bindings were generated with bindgen rather than the pregenerated copy
+    pub const BINDGEN: bool = cfg!(feature = "bindgen");
+    /// C++ compiler that built RE2 and the shim; empty in docs-only builds
+    pub const CXX_COMPILER: &str = env!("RE2_RS_CXX_COMPILER");
+    /// Flags it was given, apart from include paths and per-library defines
+    pub const CXX_FLAGS: &str = env!("RE2_RS_CXX_FLAGS");
+}
+
+// docs-only builds (see build.rs): every function of c-bindings.h is defined by an aborting stub
+#[cfg(re2_docs_only)]
+#[allow(unused_variables, clippy::all)]
+mod docs_stubs {
+    use super::*;
+
+    fn docs_only_stub(name: &str) -> ! {
+        eprintln!("{} called, but re2-rs-sys was built in docs-only mode without RE2", name);
+        std::process::abort()
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/docs_stubs.rs"));
+}
@@ -0,0 +1,150 @@
+//! RE2's parsed regular expression (`re2::Regexp`) mirrored as a Rust tree.
+//!
+//! The tree is exactly what RE2's parser produced, so tools walking it see the same
+//! structure RE2 compiles, e.g. `a{2,3}` stays a [`Ast::Repeat`] and `[a-cx]`
+//! becomes a [`Ast::CharClass`] of sorted ranges.
+//!
+//! Built with `system-re2`, RE2's parser internals are not available and every
+//! function here fails with [`Error::Internal`].
+
+use crate::error::Error;
+use crate::wrapper;
+use std::ops::{BitOr, BitOrAssign};
+
+/// Flags controlling the parser, mirrors `re2::Regexp::ParseFlags`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseFlags(u32);
+
+impl ParseFlags {
+    pub const NONE: Self = Self(0);
+    /// Fold case during matching (case-insensitive)
+    pub const FOLD_CASE: Self = Self(1 << 0);
+    /// Treat the pattern as a literal string
+    pub const LITERAL: Self = Self(1 << 1);
+    /// Allow char classes like `[^a-z]` and `\D` to match newline
+    pub const CLASS_NL: Self = Self(1 << 2);
+    /// Allow `.` to match newline
+    pub const DOT_NL: Self = Self(1 << 3);
+    pub const MATCH_NL: Self = Self(Self::CLASS_NL.0 | Self::DOT_NL.0);
+    /// `^` and `$` only match at the beginning and end of the text
+    pub const ONE_LINE: Self = Self(1 << 4);
+    /// Pattern and text are Latin-1, not UTF-8
+    pub const LATIN1: Self = Self(1 << 5);
+    /// Repetition operators are non-greedy by default
+    pub const NON_GREEDY: Self = Self(1 << 6);
+    /// Allow Perl character classes like `\d`
+    pub const PERL_CLASSES: Self = Self(1 << 7);
+    /// Allow Perl's `\b` and `\B`
+    pub const PERL_B: Self = Self(1 << 8);
+    /// Perl extensions: non-capturing groups, flag groups, `\A \z \C \Q \E`, etc.
+    pub const PERL_X: Self = Self(1 << 9);
+    /// Allow `\p{Han}` style Unicode groups
+    pub const UNICODE_GROUPS: Self = Self(1 << 10);
+    /// Never match newline, even if the pattern mentions it
+    pub const NEVER_NL: Self = Self(1 << 11);
+    /// Parse all parentheses as non-capturing
+    pub const NEVER_CAPTURE: Self = Self(1 << 12);
+    /// The flags `RE2` uses for its default (Perl-like) syntax
+    pub const LIKE_PERL: Self = Self(
+        Self::CLASS_NL.0 | Self::ONE_LINE.0 | Self::PERL_CLASSES.0 | Self::PERL_B.0
+            | Self::PERL_X.0 | Self::UNICODE_GROUPS.0,
+    );
+    /// Set by the parser on [`Ast::EndText`] nodes written as `$`
+    pub const WAS_DOLLAR: Self = Self(1 << 13);
+
+    const ALL: u32 = (1 << 14) - 1;
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn from_bits_truncate(bits: u32) -> Self {
+        Self(bits & Self::ALL)
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl Default for ParseFlags {
+    fn default() -> Self {
+        Self::LIKE_PERL
+    }
+}
+
+impl BitOr for ParseFlags {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ParseFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Inclusive range of code points in a [`Ast::CharClass`]
+///
+/// Bounds are `u32` because RE2 classes may cover surrogates, which `char` cannot hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClassRange {
+    pub lo: u32,
+    pub hi: u32,
+}
+
+/// A node of RE2's parse tree, one variant per `re2::RegexpOp`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Ast {
+    /// Matches no strings
+    NoMatch,
+    /// Matches the empty string
+    EmptyMatch,
+    Literal { c: char, fold_case: bool },
+    LiteralString { s: String, fold_case: bool },
+    Concat(Vec<Ast>),
+    Alternate(Vec<Ast>),
+    Star { sub: Box<Ast>, greedy: bool },
+    Plus { sub: Box<Ast>, greedy: bool },
+    Quest { sub: Box<Ast>, greedy: bool },
+    /// Counted repetition; `max` is `None` when unbounded
+    Repeat { sub: Box<Ast>, min: u32, max: Option<u32>, greedy: bool },
+    /// Capturing group; `index` starts at 1
+    Capture { sub: Box<Ast>, index: usize, name: Option<String> },
+    /// Any character (`.` with `(?s)`)
+    AnyChar,
+    /// Any byte (`\C`)
+    AnyByte,
+    BeginLine,
+    EndLine,
+    WordBoundary,
+    NoWordBoundary,
+    BeginText,
+    /// End of text; `was_dollar` is set when written as `$` rather than `\z`
+    EndText { was_dollar: bool },
+    CharClass(Vec<ClassRange>),
+    /// Internal marker used by `RE2::Set`; does not occur in parsed patterns
+    HaveMatch(usize),
+}
+
+/// Parse `pattern` with RE2's parser and return its tree
+pub fn parse(pattern: &str, flags: ParseFlags) -> Result<Ast, Error> {
+    wrapper::regexp_parse(pattern, flags)
+}
+
+/// Canonical form of `pattern`, as printed by RE2's `Regexp::ToString`
+///
+/// Patterns that parse to the same tree print the same way, e.g. `[cba]` and `[a-c]`.
+/// The output is wrapped in `(?s:...)` and/or `(?m:...)` when it relies on those flags,
+/// so it matches the same texts as `pattern` under Perl syntax.
+pub fn canonicalize(pattern: &str, flags: ParseFlags) -> Result<String, Error> {
+    wrapper::regexp_to_string(pattern, flags, false)
+}
+
+/// Like [`canonicalize`], but after RE2's `Simplify` pass: counted repeats are expanded
+/// and empty or full classes are normalised, so more equivalent patterns coincide.
+pub fn simplify(pattern: &str, flags: ParseFlags) -> Result<String, Error> {
+    wrapper::regexp_to_string(pattern, flags, true)
+}
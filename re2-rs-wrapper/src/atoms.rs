@@ -0,0 +1,118 @@
+//! Multi-literal matcher (Aho-Corasick) used to find `FilteredRE2` atoms in a text.
+//!
+//! Transitions are stored sparsely so memory stays proportional to the total atom length,
+//! which matters once rule sets reach tens of thousands of patterns.
+
+const ROOT: u32 = 0;
+const NONE: u32 = u32::MAX;
+
+struct Node {
+    // Sorted by byte for binary search
+    next: Vec<(u8, u32)>,
+    fail: u32,
+    // Nearest node on the failure chain that has outputs
+    dict: u32,
+    out: Vec<u32>,
+}
+
+impl Node {
+    fn new() -> Self {
+        Node { next: Vec::new(), fail: ROOT, dict: NONE, out: Vec::new() }
+    }
+
+    fn get(&self, b: u8) -> Option<u32> {
+        self.next
+            .binary_search_by_key(&b, |&(k, _)| k)
+            .ok()
+            .map(|i| self.next[i].1)
+    }
+}
+
+pub(crate) struct AtomMatcher {
+    nodes: Vec<Node>,
+    num_atoms: usize,
+}
+
+impl AtomMatcher {
+    pub(crate) fn new(atoms: &[String]) -> Self {
+        let mut nodes = vec![Node::new()];
+        for (id, atom) in atoms.iter().enumerate() {
+            let mut cur = ROOT;
+            for &b in atom.as_bytes() {
+                cur = match nodes[cur as usize].get(b) {
+                    Some(n) => n,
+                    None => {
+                        let n = nodes.len() as u32;
+                        nodes.push(Node::new());
+                        let next = &mut nodes[cur as usize].next;
+                        let pos = next.partition_point(|&(k, _)| k < b);
+                        next.insert(pos, (b, n));
+                        n
+                    }
+                };
+            }
+            nodes[cur as usize].out.push(id as u32);
+        }
+
+        // Breadth-first, so every failure target is finished before it is used.
+        let mut queue = std::collections::VecDeque::new();
+        for &(_, child) in &nodes[ROOT as usize].next {
+            queue.push_back(child);
+        }
+        while let Some(u) = queue.pop_front() {
+            for i in 0..nodes[u as usize].next.len() {
+                let (b, v) = nodes[u as usize].next[i];
+                let mut f = nodes[u as usize].fail;
+                let fail = loop {
+                    if let Some(t) = nodes[f as usize].get(b).filter(|&t| t != v) {
+                        break t;
+                    }
+                    if f == ROOT {
+                        break ROOT;
+                    }
+                    f = nodes[f as usize].fail;
+                };
+                let fnode = &nodes[fail as usize];
+                let dict = if fnode.out.is_empty() { fnode.dict } else { fail };
+                nodes[v as usize].fail = fail;
+                nodes[v as usize].dict = dict;
+                queue.push_back(v);
+            }
+        }
+
+        AtomMatcher { nodes, num_atoms: atoms.len() }
+    }
+
+    /// Indices of atoms occurring anywhere in `haystack`, ascending and distinct
+    pub(crate) fn find(&self, haystack: &[u8]) -> Vec<usize> {
+        let mut found = vec![false; self.num_atoms];
+        let mut visited = vec![false; self.nodes.len()];
+        let mut state = ROOT;
+        self.report(ROOT, &mut found, &mut visited);
+        for &b in haystack {
+            loop {
+                if let Some(n) = self.nodes[state as usize].get(b) {
+                    state = n;
+                    break;
+                }
+                if state == ROOT {
+                    break;
+                }
+                state = self.nodes[state as usize].fail;
+            }
+            self.report(state, &mut found, &mut visited);
+        }
+        (0..self.num_atoms).filter(|&i| found[i]).collect()
+    }
+
+    fn report(&self, mut node: u32, found: &mut [bool], visited: &mut [bool]) {
+        // Once a node has been reported, so has its whole dictionary chain.
+        while node != NONE && !visited[node as usize] {
+            visited[node as usize] = true;
+            for &id in &self.nodes[node as usize].out {
+                found[id as usize] = true;
+            }
+            node = self.nodes[node as usize].dict;
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! What RE2 and its dependencies were built from, for support requests and status pages.
+
+use crate::wrapper;
+use re2_rs_sys::build_info as sys;
+use std::fmt;
+
+/// Versions and build settings of the native code linked into this binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// RE2 release, `2025-08-12` when vendored or `system <version>` with `system-re2`
+    pub re2_version: &'static str,
+    /// Abseil LTS release the shim was compiled against, e.g. `20250512.1`
+    pub abseil_version: String,
+    /// ICU library version linked at runtime, if [`crate::has_icu`]
+    pub icu_version: Option<String>,
+    /// Version of the Unicode data in that ICU library
+    pub unicode_version: Option<String>,
+    /// Whether the bindings were generated with bindgen at build time
+    pub bindgen: bool,
+    /// C++ compiler that built RE2, Abseil and the shim
+    pub cxx_compiler: &'static str,
+    /// Its flags, without include paths and per-library defines
+    pub cxx_flags: &'static str,
+}
+
+/// Collect the [`BuildInfo`] of this binary
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        re2_version: sys::RE2_VERSION,
+        abseil_version: wrapper::abseil_version(),
+        icu_version: wrapper::icu_version(),
+        unicode_version: wrapper::icu_unicode_version(),
+        bindgen: sys::BINDGEN,
+        cxx_compiler: sys::CXX_COMPILER,
+        cxx_flags: sys::CXX_FLAGS,
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RE2 {}, Abseil {}", self.re2_version, self.abseil_version)?;
+        match (&self.icu_version, &self.unicode_version) {
+            (Some(icu), Some(unicode)) => write!(f, ", ICU {} (Unicode {})", icu, unicode)?,
+            (Some(icu), None) => write!(f, ", ICU {}", icu)?,
+            _ => write!(f, ", no ICU")?,
+        }
+        let bindings = if self.bindgen { "bindgen" } else { "pregenerated" };
+        write!(f, ", {} bindings, {} {}", bindings, self.cxx_compiler, self.cxx_flags)
+    }
+}
@@ -0,0 +1,35 @@
+//! RE2's compiled program (`re2::Prog`) for a [`Regex`], as text for debugging.
+//!
+//! Each function recompiles the forward program the regex matches with, so the
+//! output is exactly what RE2 runs but costs as much as compiling the pattern.
+//! The format is RE2's own and not stable across RE2 versions. Built with `system-re2`,
+//! `re2::Prog` is not available and every function here returns an empty string.
+
+use crate::regex::Regex;
+use crate::wrapper;
+
+/// `Prog::Dump()`: the anchored program, one instruction per line
+///
+/// Lines read `id. op` for the last entry of an instruction list and `id+ op` otherwise.
+pub fn dump(re: &Regex) -> String {
+    wrapper::prog_dump(re.as_raw()).unwrap_or_default()
+}
+
+/// `Prog::DumpUnanchored()`: as [`dump`], starting from the unanchored entry point
+/// (the `.*?` loop RE2 prepends for partial matches)
+pub fn dump_unanchored(re: &Regex) -> String {
+    wrapper::prog_dump_unanchored(re.as_raw()).unwrap_or_default()
+}
+
+/// `Prog::DumpByteMap()`: the byte classes the DFA works on, one `[lo-hi] -> class` per line
+pub fn dump_byte_map(re: &Regex) -> String {
+    wrapper::prog_dump_byte_map(re.as_raw()).unwrap_or_default()
+}
+
+/// Graphviz DOT of the instruction graph
+///
+/// Solid edges follow an instruction's `out`; dashed edges link the entries of
+/// one instruction list, which are tried in order. Render with `dot -Tsvg`.
+pub fn to_dot(re: &Regex) -> String {
+    wrapper::prog_dot(re.as_raw()).unwrap_or_default()
+}
@@ -0,0 +1,45 @@
+use crate::policy::PolicyLimit;
+use std::fmt;
+
+/// Errors reported by RE2 through the wrapper
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// RE2 rejected the pattern; carries RE2's error message
+    Compile(String),
+    /// RE2 ran out of memory (compiler or DFA budget exhausted, or an allocation failed)
+    OutOfMemory,
+    /// RE2 threw a C++ exception other than `std::bad_alloc`, or reported an
+    /// inconsistent internal state; carries a description
+    Internal(String),
+    /// A set was matched before `compile()` succeeded
+    NotCompiled,
+    /// A pattern was added to a set after `compile()`
+    AlreadyCompiled,
+    /// An atom index passed to a [`crate::FilteredRegexSet`] is not in its atom list
+    AtomOutOfRange { index: usize, len: usize },
+    /// The text is longer than RE2 supports for this operation
+    TextTooLarge { len: usize, max: usize },
+    /// The pattern exceeded a [`crate::CompilePolicy`] limit
+    Policy(PolicyLimit),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Compile(msg) => write!(f, "RE2 compile error: {}", msg),
+            Error::OutOfMemory => f.write_str("RE2 ran out of memory"),
+            Error::Internal(msg) => write!(f, "RE2 internal error: {}", msg),
+            Error::NotCompiled => f.write_str("set has not been compiled"),
+            Error::AlreadyCompiled => f.write_str("set has already been compiled"),
+            Error::AtomOutOfRange { index, len } => {
+                write!(f, "atom index {} out of range ({} atoms)", index, len)
+            }
+            Error::TextTooLarge { len, max } => {
+                write!(f, "text of {} bytes exceeds the limit of {} bytes", len, max)
+            }
+            Error::Policy(limit) => write!(f, "compile policy violated: {}", limit),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,152 @@
+use crate::atoms::AtomMatcher;
+use crate::error::Error;
+use crate::wrapper::{self, Options, RE2FilteredHandle};
+
+struct Compiled {
+    atoms: Vec<String>,
+    matcher: AtomMatcher,
+}
+
+/// Prefilter engine for large rule sets, backed by `FilteredRE2`
+///
+/// Each pattern is reduced to a set of literal "atoms". After [`FilteredRegexSet::compile`],
+/// the caller searches the text for those atoms with any literal matcher, then passes the
+/// indices of the atoms it found to [`FilteredRegexSet::all_matches`]. Only patterns whose
+/// atoms occurred are run.
+///
+/// Atoms are lowercased, so the atom search must be case-insensitive
+/// (or run over a lowercased copy of the text).
+///
+/// For the common case, [`FilteredRegexSet::matches`] does the atom search itself with a
+/// built-in multi-literal automaton, so no external string matcher is needed.
+pub struct FilteredRegexSet {
+    raw: RE2FilteredHandle,
+    compiled: Option<Compiled>,
+}
+
+impl FilteredRegexSet {
+    /// Atoms shorter than `min_atom_len` bytes are dropped; patterns relying on them are
+    /// always treated as potential matches.
+    pub fn new(min_atom_len: usize) -> Result<Self, Error> {
+        let raw = wrapper::filtered_new(min_atom_len).ok_or(Error::OutOfMemory)?;
+        Ok(Self { raw, compiled: None })
+    }
+
+    /// Add a pattern, returning its id
+    pub fn add(&mut self, pattern: &str, opts: &Options) -> Result<usize, Error> {
+        if self.compiled.is_some() {
+            return Err(Error::AlreadyCompiled);
+        }
+        wrapper::filtered_add(self.raw, pattern, opts)
+    }
+
+    /// Prepare the patterns for filtering and return the atoms to search for.
+    /// Atom indices passed to the matching functions index into this list.
+    pub fn compile(&mut self) -> Result<Vec<String>, Error> {
+        if self.compiled.is_some() {
+            return Err(Error::AlreadyCompiled);
+        }
+        let atoms = wrapper::filtered_compile(self.raw)?;
+        let matcher = AtomMatcher::new(&atoms);
+        self.compiled = Some(Compiled { atoms: atoms.clone(), matcher });
+        Ok(atoms)
+    }
+
+    /// Atoms computed by [`FilteredRegexSet::compile`], if compiled
+    pub fn atoms(&self) -> Option<&[String]> {
+        self.compiled.as_ref().map(|c| c.atoms.as_slice())
+    }
+
+    /// Indices of the atoms occurring in `text`, found with the built-in matcher
+    pub fn find_atoms(&self, text: &str) -> Result<Vec<usize>, Error> {
+        let compiled = self.compiled.as_ref().ok_or(Error::NotCompiled)?;
+        // Atoms are lowercased by RE2, so search a lowercased copy of the text. RE2 lowers
+        // one rune at a time with the simple mapping, so str::to_lowercase would not do:
+        // it turns a word-final 'Σ' into 'ς' where RE2 has 'σ'. The simple mapping is the
+        // first char of the full one ('İ' lowers to 'i' plus a combining dot).
+        let lowered: String = if text.is_ascii() {
+            text.to_ascii_lowercase()
+        } else {
+            text.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect()
+        };
+        Ok(compiled.matcher.find(lowered.as_bytes()))
+    }
+
+    /// Every pattern matching `text`: finds the atoms, then runs only the candidate patterns
+    pub fn matches(&self, text: &str) -> Result<Vec<usize>, Error> {
+        let found = self.find_atoms(text)?;
+        self.all_matches(text, &found)
+    }
+
+    /// True if any pattern matches `text`
+    pub fn is_match(&self, text: &str) -> Result<bool, Error> {
+        let found = self.find_atoms(text)?;
+        self.first_match(text, &found).map(|m| m.is_some())
+    }
+
+    /// Number of patterns added
+    pub fn len(&self) -> usize {
+        wrapper::filtered_size(self.raw)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// First pattern matching `text`, trying every pattern without filtering.
+    /// Can be called before compiling.
+    pub fn slow_first_match(&self, text: &str) -> Result<Option<usize>, Error> {
+        wrapper::filtered_slow_first_match(self.raw, text)
+    }
+
+    /// First pattern matching `text`, given the indices of atoms found in it
+    ///
+    /// # Errors
+    /// [`Error::AtomOutOfRange`] if an atom index is out of range for the compiled atom list.
+    pub fn first_match(&self, text: &str, atoms: &[usize]) -> Result<Option<usize>, Error> {
+        let atoms = self.atom_ids(atoms)?;
+        if self.is_empty() {
+            return Ok(None);
+        }
+        wrapper::filtered_first_match(self.raw, text, &atoms)
+    }
+
+    /// Every pattern matching `text`, given the indices of atoms found in it
+    ///
+    /// # Errors
+    /// [`Error::AtomOutOfRange`] if an atom index is out of range for the compiled atom list.
+    pub fn all_matches(&self, text: &str, atoms: &[usize]) -> Result<Vec<usize>, Error> {
+        let atoms = self.atom_ids(atoms)?;
+        wrapper::filtered_all_matches(self.raw, text, &atoms)
+    }
+
+    /// Every pattern that passes the filter for the given atoms. These may still not match.
+    ///
+    /// # Errors
+    /// [`Error::AtomOutOfRange`] if an atom index is out of range for the compiled atom list.
+    pub fn all_potentials(&self, atoms: &[usize]) -> Result<Vec<usize>, Error> {
+        let atoms = self.atom_ids(atoms)?;
+        wrapper::filtered_all_potentials(self.raw, &atoms)
+    }
+
+    // RE2 indexes its atom table unchecked, so validate here.
+    fn atom_ids(&self, atoms: &[usize]) -> Result<Vec<i32>, Error> {
+        let compiled = &self.compiled.as_ref().ok_or(Error::NotCompiled)?.atoms;
+        atoms
+            .iter()
+            .map(|&index| {
+                if index < compiled.len() {
+                    Ok(index as i32)
+                } else {
+                    Err(Error::AtomOutOfRange { index, len: compiled.len() })
+                }
+            })
+            .collect()
+    }
+}
+
+impl Drop for FilteredRegexSet {
+    fn drop(&mut self) {
+        wrapper::filtered_delete(self.raw)
+    }
+}
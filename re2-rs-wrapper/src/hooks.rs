@@ -0,0 +1,82 @@
+//! Process-wide callbacks for RE2's `re2::hooks`, fired when a DFA runs out of memory.
+//!
+//! A pattern whose DFA keeps resetting its state cache, or gives up and falls back
+//! to the NFA, still matches correctly but much more slowly. These hooks make that
+//! visible, e.g. to count it in metrics.
+//!
+//! Callbacks run on the matching thread while RE2 holds the DFA's cache lock: keep
+//! them short, and do not match with RE2 from inside one. Panics are caught and dropped.
+
+use crate::wrapper;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, RwLock};
+
+/// Reported when a DFA exhausts its memory budget and discards its cached states
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DfaCacheReset {
+    /// Bytes the DFA may spend on states (its share of the regex's `max_mem`)
+    pub state_budget: i64,
+    /// Number of cached states discarded
+    pub state_cache_size: usize,
+    /// Pattern being matched, if RE2 knows it (not inside a [`crate::RegexSet`])
+    pub pattern: Option<String>,
+}
+
+/// Reported when a DFA search fails and RE2 falls back to a slower engine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DfaSearchFailure {
+    /// Pattern being matched, if RE2 knows it (not inside a [`crate::RegexSet`])
+    pub pattern: Option<String>,
+}
+
+type Callback<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+static DFA_CACHE_RESET: RwLock<Option<Callback<DfaCacheReset>>> = RwLock::new(None);
+static DFA_SEARCH_FAILURE: RwLock<Option<Callback<DfaSearchFailure>>> = RwLock::new(None);
+
+/// Call `callback` whenever a DFA resets its state cache, replacing any previous callback
+pub fn on_dfa_cache_reset<F>(callback: F)
+where
+    F: Fn(&DfaCacheReset) + Send + Sync + 'static,
+{
+    *DFA_CACHE_RESET.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(callback));
+    wrapper::set_dfa_cache_reset_hook(true);
+}
+
+/// Call `callback` whenever a DFA search fails, replacing any previous callback
+pub fn on_dfa_search_failure<F>(callback: F)
+where
+    F: Fn(&DfaSearchFailure) + Send + Sync + 'static,
+{
+    *DFA_SEARCH_FAILURE.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(callback));
+    wrapper::set_dfa_search_failure_hook(true);
+}
+
+/// Remove the callback set by [`on_dfa_cache_reset`]
+pub fn clear_dfa_cache_reset() {
+    wrapper::set_dfa_cache_reset_hook(false);
+    *DFA_CACHE_RESET.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Remove the callback set by [`on_dfa_search_failure`]
+pub fn clear_dfa_search_failure() {
+    wrapper::set_dfa_search_failure_hook(false);
+    *DFA_SEARCH_FAILURE.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+pub(crate) fn dispatch_dfa_cache_reset(info: &DfaCacheReset) {
+    dispatch(&DFA_CACHE_RESET, info)
+}
+
+pub(crate) fn dispatch_dfa_search_failure(info: &DfaSearchFailure) {
+    dispatch(&DFA_SEARCH_FAILURE, info)
+}
+
+// Clone the callback out of the lock so it may itself (re)register hooks
+fn dispatch<T>(slot: &RwLock<Option<Callback<T>>>, info: &T) {
+    let callback = slot.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(callback) = callback {
+        // Unwinding into RE2's C++ frames would abort the process
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| callback(info)));
+    }
+}
@@ -1,7 +1,29 @@
 mod wrapper;
 mod regex;
+mod error;
+mod set;
+mod filtered;
+mod atoms;
+mod prefilter;
+mod pcre;
+mod policy;
+mod build_info;
+pub mod ast;
+pub mod debug;
+pub mod hooks;
+pub mod logging;
+pub mod memory;
 
 // Public API re-exports
 pub use regex::Regex;
+pub use set::RegexSet;
+pub use filtered::FilteredRegexSet;
+pub use prefilter::Prefilter;
+pub use pcre::{pcre_compatible, pcre_incompatibility, PcreIncompatibility};
+pub use policy::{CompilePolicy, PolicyLimit};
+pub use build_info::{build_info, BuildInfo};
+pub use error::Error;
+pub use wrapper::Anchor;
+pub use wrapper::Engine;
 pub use wrapper::Options;
 pub use wrapper::has_icu;
\ No newline at end of file
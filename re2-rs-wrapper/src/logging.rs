@@ -0,0 +1,86 @@
+//! Where RE2's log messages go.
+//!
+//! RE2 logs through Abseil, which writes to stderr by default: bad patterns with
+//! `log_errors` on (see [`crate::Options::log_errors`]), DFA memory exhaustion, and
+//! internal errors. [`set_logger`] redirects all of them to a Rust callback instead.
+//! With the `log` feature, [`forward_to_log`] sends them to the `log` facade.
+
+use crate::wrapper;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, RwLock};
+
+/// Severity of a log message, mirrors `absl::LogSeverity`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+    /// The process aborts after the message is delivered
+    Fatal,
+}
+
+/// A single log message from RE2 or Abseil
+#[derive(Debug, Clone, Copy)]
+pub struct LogRecord<'a> {
+    pub severity: Severity,
+    /// C++ source file that logged, e.g. `re2.cc`
+    pub file: &'a str,
+    pub line: u32,
+    pub message: &'a str,
+}
+
+type Logger = Arc<dyn Fn(&LogRecord<'_>) + Send + Sync>;
+
+static LOGGER: RwLock<Option<Logger>> = RwLock::new(None);
+
+/// Send every RE2 log message to `logger` instead of stderr, replacing any previous logger
+///
+/// The logger may run on any thread that uses RE2, sometimes with RE2 locks held:
+/// keep it short and do not use RE2 from inside it. Panics are caught and dropped.
+pub fn set_logger<F>(logger: F)
+where
+    F: Fn(&LogRecord<'_>) + Send + Sync + 'static,
+{
+    *LOGGER.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(logger));
+    wrapper::set_log_callback(true);
+}
+
+/// Remove the logger set by [`set_logger`]; messages go to stderr again
+///
+/// Abseil's stderr threshold goes back to what it was before [`set_logger`]: every
+/// message if nothing had configured Abseil logging, as when no logger was ever set.
+pub fn clear_logger() {
+    wrapper::set_log_callback(false);
+    *LOGGER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Send RE2 log messages to the `log` facade with target `re2`
+///
+/// [`Severity::Fatal`] is logged at [`log::Level::Error`].
+#[cfg(feature = "log")]
+pub fn forward_to_log() {
+    set_logger(|record| {
+        let level = match record.severity {
+            Severity::Info => log::Level::Info,
+            Severity::Warning => log::Level::Warn,
+            Severity::Error | Severity::Fatal => log::Level::Error,
+        };
+        log::logger().log(
+            &log::Record::builder()
+                .level(level)
+                .target("re2")
+                .file(Some(record.file))
+                .line(Some(record.line))
+                .args(format_args!("{}", record.message))
+                .build(),
+        );
+    });
+}
+
+pub(crate) fn dispatch(record: &LogRecord<'_>) {
+    let logger = LOGGER.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(logger) = logger {
+        // Unwinding into Abseil's C++ frames would abort the process
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| logger(record)));
+    }
+}
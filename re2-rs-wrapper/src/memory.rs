@@ -0,0 +1,31 @@
+//! Process-wide counters of live [`crate::Regex`] objects and their heap footprint.
+//!
+//! Each regex is counted at construction with [`crate::Regex::approx_heap_bytes`] and
+//! uncounted by `Drop`. The reverse program and DFA caches come later as the regex is
+//! used and are not tracked here; ask the regex itself for a current figure, or
+//! [`crate::Regex::max_heap_bytes`] for an upper bound.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static LIVE_REGEXES: AtomicUsize = AtomicUsize::new(0);
+static TOTAL_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of [`crate::Regex`] objects currently alive
+pub fn live_regexes() -> usize {
+    LIVE_REGEXES.load(Ordering::Relaxed)
+}
+
+/// Sum of the live regexes' footprints as counted at construction
+pub fn total_bytes() -> usize {
+    TOTAL_BYTES.load(Ordering::Relaxed)
+}
+
+pub(crate) fn track(bytes: usize) {
+    LIVE_REGEXES.fetch_add(1, Ordering::Relaxed);
+    TOTAL_BYTES.fetch_add(bytes, Ordering::Relaxed);
+}
+
+pub(crate) fn untrack(bytes: usize) {
+    LIVE_REGEXES.fetch_sub(1, Ordering::Relaxed);
+    TOTAL_BYTES.fetch_sub(bytes, Ordering::Relaxed);
+}
@@ -0,0 +1,114 @@
+//! Whether a pattern behaves identically under PCRE, via RE2's `Regexp::MimicsPCRE`.
+//!
+//! RE2 matches PCRE except for a handful of constructs (see `mimics_pcre.cc`):
+//! repetitions of something that can match the empty string, `\v`, `$` outside
+//! multi-line mode, and `^` in multi-line mode.
+//!
+//! Built with `system-re2` the parse tree is not available: [`pcre_compatible`] is
+//! always `false` and [`pcre_incompatibility`] fails with [`Error::Internal`].
+
+use crate::ast::{self, Ast, ParseFlags};
+use crate::error::Error;
+use crate::wrapper;
+use std::fmt;
+
+/// Flags [`crate::Regex::new`] parses with (`RE2::Options` defaults)
+const DEFAULT_FLAGS: ParseFlags = ParseFlags::LIKE_PERL;
+
+/// The construct that makes RE2 and PCRE disagree on a pattern
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PcreIncompatibility {
+    /// A `*`, `+`, `?` or unbounded `{n,}` whose operand can match the empty string,
+    /// like `(a*)*`. PCRE ends the repetition with an empty iteration, RE2 does not.
+    EmptyRepetition(Ast),
+    /// A literal `\v`: Perl (and RE2) do not match `\n` with it, PCRE does
+    VerticalTab,
+    /// `$` outside multi-line mode: PCRE also matches before a final `\n`, RE2 only at the end
+    DollarEndOfText,
+    /// `^` in multi-line mode: RE2 also matches at the end of text after a final `\n`, PCRE does not
+    MultiLineCaret,
+}
+
+impl fmt::Display for PcreIncompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PcreIncompatibility::EmptyRepetition(_) => {
+                write!(f, "repetition of an expression that can match the empty string")
+            }
+            PcreIncompatibility::VerticalTab => write!(f, "\\v matches \\n under PCRE but not under RE2"),
+            PcreIncompatibility::DollarEndOfText => {
+                write!(f, "$ outside multi-line mode matches before a final \\n under PCRE but not under RE2")
+            }
+            PcreIncompatibility::MultiLineCaret => {
+                write!(f, "^ in multi-line mode matches after a final \\n under RE2 but not under PCRE")
+            }
+        }
+    }
+}
+
+/// Whether `pattern` matches exactly as it would under PCRE
+///
+/// Parsed with the same flags as [`crate::Regex::new`]. Patterns RE2 rejects return `false`.
+pub fn pcre_compatible(pattern: &str) -> bool {
+    wrapper::regexp_mimics_pcre(pattern, DEFAULT_FLAGS).unwrap_or(false)
+}
+
+/// Explain why [`pcre_compatible`] is `false` for `pattern`
+///
+/// Returns `Ok(None)` if the pattern is compatible, or the first offending construct
+/// in RE2's post-order walk of the tree.
+pub fn pcre_incompatibility(pattern: &str) -> Result<Option<PcreIncompatibility>, Error> {
+    if wrapper::regexp_mimics_pcre(pattern, DEFAULT_FLAGS)? {
+        return Ok(None);
+    }
+    let tree = ast::parse(pattern, DEFAULT_FLAGS)?;
+    // The only check not visible in the Rust tree is an EmptyMatch carrying WasDollar,
+    // which is how RE2 records a `$` that was simplified away.
+    Ok(Some(find_incompatibility(&tree).unwrap_or(PcreIncompatibility::DollarEndOfText)))
+}
+
+// Mirrors PCREWalker::PostVisit: children first, then the node itself
+fn find_incompatibility(node: &Ast) -> Option<PcreIncompatibility> {
+    match node {
+        Ast::Concat(subs) | Ast::Alternate(subs) => subs.iter().find_map(find_incompatibility),
+        Ast::Star { sub, .. } | Ast::Plus { sub, .. } | Ast::Quest { sub, .. } => {
+            find_incompatibility(sub).or_else(|| {
+                can_be_empty(sub).then(|| PcreIncompatibility::EmptyRepetition(node.clone()))
+            })
+        }
+        Ast::Repeat { sub, max, .. } => find_incompatibility(sub).or_else(|| {
+            (max.is_none() && can_be_empty(sub)).then(|| PcreIncompatibility::EmptyRepetition(node.clone()))
+        }),
+        Ast::Capture { sub, .. } => find_incompatibility(sub),
+        Ast::Literal { c: '\x0b', .. } => Some(PcreIncompatibility::VerticalTab),
+        Ast::EndText { was_dollar: true } => Some(PcreIncompatibility::DollarEndOfText),
+        Ast::BeginLine => Some(PcreIncompatibility::MultiLineCaret),
+        _ => None,
+    }
+}
+
+// Mirrors EmptyStringWalker: may overestimate, e.g. `\b\B` counts as possibly empty
+fn can_be_empty(node: &Ast) -> bool {
+    match node {
+        Ast::NoMatch
+        | Ast::Literal { .. }
+        | Ast::LiteralString { .. }
+        | Ast::AnyChar
+        | Ast::AnyByte
+        | Ast::CharClass(_) => false,
+        Ast::EmptyMatch
+        | Ast::BeginLine
+        | Ast::EndLine
+        | Ast::WordBoundary
+        | Ast::NoWordBoundary
+        | Ast::BeginText
+        | Ast::EndText { .. }
+        | Ast::Star { .. }
+        | Ast::Quest { .. }
+        | Ast::HaveMatch(_) => true,
+        Ast::Concat(subs) => subs.iter().all(can_be_empty),
+        Ast::Alternate(subs) => subs.iter().any(can_be_empty),
+        Ast::Plus { sub, .. } | Ast::Capture { sub, .. } => can_be_empty(sub),
+        Ast::Repeat { sub, min, .. } => *min == 0 || can_be_empty(sub),
+    }
+}
@@ -0,0 +1,143 @@
+//! Limits for compiling untrusted patterns, see [`crate::Regex::with_policy`].
+//!
+//! RE2 already guarantees linear-time matching, but a short pattern can still compile
+//! to a large program (`(?:a{1000}){1000}` is rejected by the parser, `\pL{100}` is not).
+//! A [`CompilePolicy`] bounds what a pattern may cost before it is accepted.
+
+use crate::error::Error;
+use crate::wrapper::{self, Options, RE2WrapperHandle};
+use std::fmt;
+
+/// Limits a pattern must stay within to compile; every limit is off by default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompilePolicy {
+    max_pattern_len: Option<usize>,
+    max_program_size: Option<usize>,
+    max_repeat: Option<u32>,
+    max_captures: Option<usize>,
+    max_mem: Option<i64>,
+}
+
+impl CompilePolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Longest pattern accepted, in bytes. Checked before RE2 sees the pattern.
+    pub fn max_pattern_len(mut self, bytes: usize) -> Self {
+        self.max_pattern_len = Some(bytes);
+        self
+    }
+
+    /// Largest compiled program accepted, in instructions (`RE2::ProgramSize`)
+    pub fn max_program_size(mut self, instructions: usize) -> Self {
+        self.max_program_size = Some(instructions);
+        self
+    }
+
+    /// Largest count a `{n,m}` repetition may expand to. Nested repetitions multiply,
+    /// as in RE2's own parser limit of 1000, so `(?:a{10}){10}` counts as 100. A bound of
+    /// 0 (`{0,}` or `{0}`) is skipped, so it cannot hide the counts inside it.
+    /// Counting needs RE2's parsed pattern, so with `system-re2` a policy with this limit
+    /// rejects every pattern with [`Error::Internal`].
+    pub fn max_repeat(mut self, count: u32) -> Self {
+        self.max_repeat = Some(count);
+        self
+    }
+
+    /// Most capturing groups accepted
+    pub fn max_captures(mut self, groups: usize) -> Self {
+        self.max_captures = Some(groups);
+        self
+    }
+
+    /// Ceiling on the options' `max_mem`: a lower value in [`Options::max_mem`] is kept,
+    /// a higher one is clamped. A program that does not fit fails with [`PolicyLimit::MaxMem`].
+    pub fn max_mem(mut self, bytes: i64) -> Self {
+        self.max_mem = Some(bytes);
+        self
+    }
+
+    pub(crate) fn compile(&self, pattern: &str, opts: &Options) -> Result<RE2WrapperHandle, Error> {
+        if let Some(max) = self.max_pattern_len
+            && pattern.len() > max
+        {
+            return Err(Error::Policy(PolicyLimit::PatternLength { max, actual: pattern.len() }));
+        }
+
+        let mut opts = opts.clone();
+        if let Some(max) = self.max_mem
+            && opts.get_max_mem() > max
+        {
+            opts = opts.max_mem(max);
+        }
+        let raw = wrapper::compile_regex_with_code(pattern, Some(&opts)).map_err(|(err, code)| {
+            if self.max_mem.is_some() && code == wrapper::ERROR_PATTERN_TOO_LARGE {
+                Error::Policy(PolicyLimit::MaxMem { max: opts.get_max_mem() })
+            } else {
+                err
+            }
+        })?;
+
+        self.check_compiled(raw).inspect_err(|_| wrapper::delete_regex(raw))?;
+        Ok(raw)
+    }
+
+    fn check_compiled(&self, raw: RE2WrapperHandle) -> Result<(), Error> {
+        if let Some(max) = self.max_captures {
+            let actual = wrapper::group_count(raw);
+            if actual > max {
+                return Err(Error::Policy(PolicyLimit::CaptureCount { max, actual }));
+            }
+        }
+        if let Some(max) = self.max_repeat {
+            let actual = wrapper::max_repeat(raw)?;
+            if actual > max {
+                return Err(Error::Policy(PolicyLimit::RepeatCount { max, actual }));
+            }
+        }
+        if let Some(max) = self.max_program_size {
+            let actual = wrapper::program_size(raw);
+            if actual > max {
+                return Err(Error::Policy(PolicyLimit::ProgramSize { max, actual }));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The [`CompilePolicy`] limit a pattern exceeded, with the limit and the pattern's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PolicyLimit {
+    /// Pattern length in bytes
+    PatternLength { max: usize, actual: usize },
+    /// Compiled program size in instructions
+    ProgramSize { max: usize, actual: usize },
+    /// Effective count of the largest (nested) counted repetition
+    RepeatCount { max: u32, actual: u32 },
+    /// Number of capturing groups
+    CaptureCount { max: usize, actual: usize },
+    /// The compiled program needs more than the effective `max_mem` of `max` bytes;
+    /// RE2 does not report how much
+    MaxMem { max: i64 },
+}
+
+impl fmt::Display for PolicyLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyLimit::PatternLength { max, actual } => {
+                write!(f, "pattern length {} exceeds the limit of {} bytes", actual, max)
+            }
+            PolicyLimit::ProgramSize { max, actual } => {
+                write!(f, "program size {} exceeds the limit of {} instructions", actual, max)
+            }
+            PolicyLimit::RepeatCount { max, actual } => {
+                write!(f, "repeat count {} exceeds the limit of {}", actual, max)
+            }
+            PolicyLimit::CaptureCount { max, actual } => {
+                write!(f, "{} capturing groups exceed the limit of {}", actual, max)
+            }
+            PolicyLimit::MaxMem { max } => write!(f, "compiled program exceeds max_mem of {} bytes", max),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+/// The literal query a regex implies, computed by RE2's `Prefilter::FromRE2`
+///
+/// Any text matching the regex satisfies this query, so it can be run against a
+/// literal/trigram index to find candidate documents. Atoms are lowercased:
+/// compare them case-insensitively.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Prefilter {
+    /// Every text is a candidate
+    All,
+    /// No text can match
+    None,
+    /// The text must contain this literal
+    Atom(String),
+    /// Every sub-query must hold
+    And(Vec<Prefilter>),
+    /// At least one sub-query must hold
+    Or(Vec<Prefilter>),
+}
+
+impl Prefilter {
+    /// Drop atoms shorter than `min_atom_len` bytes and simplify the tree.
+    ///
+    /// A short atom becomes [`Prefilter::All`], which is then absorbed by an enclosing
+    /// `And` or takes over an enclosing `Or`, the same way RE2's `PrefilterTree` prunes.
+    pub fn prune(self, min_atom_len: usize) -> Prefilter {
+        match self {
+            Prefilter::Atom(a) if a.len() < min_atom_len => Prefilter::All,
+            Prefilter::And(subs) => {
+                let mut kept = Vec::with_capacity(subs.len());
+                for sub in subs {
+                    match sub.prune(min_atom_len) {
+                        Prefilter::All => {}
+                        Prefilter::None => return Prefilter::None,
+                        Prefilter::And(inner) => kept.extend(inner),
+                        p => kept.push(p),
+                    }
+                }
+                Self::collapse(kept, Prefilter::All, Prefilter::And)
+            }
+            Prefilter::Or(subs) => {
+                let mut kept = Vec::with_capacity(subs.len());
+                for sub in subs {
+                    match sub.prune(min_atom_len) {
+                        Prefilter::All => return Prefilter::All,
+                        Prefilter::None => {}
+                        Prefilter::Or(inner) => kept.extend(inner),
+                        p => kept.push(p),
+                    }
+                }
+                Self::collapse(kept, Prefilter::None, Prefilter::Or)
+            }
+            p => p,
+        }
+    }
+
+    /// Distinct atoms anywhere in the tree, in first-seen order
+    pub fn atoms(&self) -> Vec<&str> {
+        let mut out = Vec::new();
+        self.collect_atoms(&mut out);
+        out
+    }
+
+    fn collect_atoms<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Prefilter::Atom(a) => {
+                if !out.contains(&a.as_str()) {
+                    out.push(a);
+                }
+            }
+            Prefilter::And(subs) | Prefilter::Or(subs) => {
+                subs.iter().for_each(|s| s.collect_atoms(out))
+            }
+            Prefilter::All | Prefilter::None => {}
+        }
+    }
+
+    fn collapse(mut subs: Vec<Prefilter>, empty: Prefilter, node: fn(Vec<Prefilter>) -> Prefilter) -> Prefilter {
+        match subs.len() {
+            0 => empty,
+            1 => subs.pop().unwrap(),
+            _ => node(subs),
+        }
+    }
+}
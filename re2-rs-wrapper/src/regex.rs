@@ -1,51 +1,174 @@
+use crate::error::Error;
+use crate::memory;
+use crate::policy::CompilePolicy;
+use crate::prefilter::Prefilter;
 use crate::wrapper::{self, RE2WrapperHandle};
 
 /// Safe Rust wrapper around RE2
+///
+/// Matching methods treat a failure inside RE2, such as a failed allocation or a C++
+/// exception, as no match. Their `try_` variants return it as an [`Error`] instead.
+///
+/// Texts of any length can be matched: positions are passed to RE2 as 64-bit offsets.
+/// Only [`Regex::replace_all`] has a limit, [`Regex::MAX_REPLACE_ALL_LEN`] bytes.
 pub struct Regex {
     raw: RE2WrapperHandle,
+    // Bytes added to `memory::total_bytes` at construction, removed again on drop
+    counted_bytes: usize,
 }
 
 impl Regex {
-    pub fn new(pattern: &str) -> Result<Self, String> {
-        wrapper::compile_regex(pattern, None).map(|raw| Self { raw })
+    /// Longest text [`Regex::replace_all`] accepts. RE2 counts the replacements in an
+    /// `int`, and a text of n bytes can have n + 1 (empty) matches.
+    pub const MAX_REPLACE_ALL_LEN: usize = i32::MAX as usize - 1;
+
+    /// Compile `pattern` with default options
+    ///
+    /// Fails with [`Error::Compile`] for an invalid pattern, or [`Error::OutOfMemory`] /
+    /// [`Error::Internal`] if RE2 could not allocate or threw.
+    ///
+    /// The error used to be a `String` holding RE2's message. That message is now the
+    /// payload of [`Error::Compile`]; [`Error`]'s `Display` prints it with a
+    /// "RE2 compile error: " prefix.
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        wrapper::compile_regex(pattern, None).map(Self::from_raw)
+    }
+
+    /// Compile `pattern` with `opts`. Fails as [`Regex::new`] does.
+    pub fn with_options(pattern: &str, opts: &wrapper::Options) -> Result<Self, Error> {
+        wrapper::compile_regex(pattern, Some(opts)).map(Self::from_raw)
+    }
+
+    /// Compile an untrusted `pattern`, failing with [`Error::Policy`] if it exceeds
+    /// any limit of `policy`. See [`CompilePolicy`].
+    pub fn with_policy(pattern: &str, opts: &wrapper::Options, policy: &CompilePolicy) -> Result<Self, Error> {
+        policy.compile(pattern, opts).map(Self::from_raw)
+    }
+
+    fn from_raw(raw: RE2WrapperHandle) -> Self {
+        let counted_bytes = wrapper::approx_heap_bytes(raw);
+        memory::track(counted_bytes);
+        Self { raw, counted_bytes }
     }
 
-    pub fn with_options(pattern: &str, opts: &wrapper::Options) -> Result<Self, String> {
-        wrapper::compile_regex(pattern, Some(opts)).map(|raw| Self { raw })
+    pub(crate) fn as_raw(&self) -> RE2WrapperHandle {
+        self.raw
     }
 
     pub fn full_match(&self, text: &str) -> bool {
+        self.try_full_match(text).unwrap_or(false)
+    }
+
+    /// Like [`Regex::full_match`], failing with [`Error::OutOfMemory`] or
+    /// [`Error::Internal`] if RE2 could not allocate or threw
+    pub fn try_full_match(&self, text: &str) -> Result<bool, Error> {
         wrapper::full_match(self.raw, text)
     }
 
     pub fn partial_match(&self, text: &str) -> bool {
+        self.try_partial_match(text).unwrap_or(false)
+    }
+
+    /// Like [`Regex::partial_match`], but surfaces failures inside RE2
+    pub fn try_partial_match(&self, text: &str) -> Result<bool, Error> {
         wrapper::partial_match(self.raw, text)
     }
 
     pub fn partial_captures<'t>(&self, text: &'t str) -> Option<Vec<Option<&'t str>>> {
+        self.try_partial_captures(text).ok().flatten()
+    }
+
+    /// Like [`Regex::partial_captures`], but surfaces failures inside RE2
+    pub fn try_partial_captures<'t>(&self, text: &'t str) -> Result<Option<Vec<Option<&'t str>>>, Error> {
         wrapper::captures(self.raw, text, false)
     }
 
     pub fn full_captures<'t>(&self, text: &'t str) -> Option<Vec<Option<&'t str>>> {
+        self.try_full_captures(text).ok().flatten()
+    }
+
+    /// Like [`Regex::full_captures`], but surfaces failures inside RE2
+    pub fn try_full_captures<'t>(&self, text: &'t str) -> Result<Option<Vec<Option<&'t str>>>, Error> {
         wrapper::captures(self.raw, text, true)
     }
 
+    /// Approximate heap bytes held by this regex now: the RE2 object, its pattern, its
+    /// forward program, the reverse program once matching has compiled it, and what the
+    /// DFA caches hold. Measuring builds nothing. Must not be called from a
+    /// [`crate::hooks::on_dfa_cache_reset`] callback for a reset of this regex's DFA.
+    /// With `system-re2` RE2's internals are out of reach, so only the object, pattern
+    /// and an estimate of the forward program are counted.
+    pub fn approx_heap_bytes(&self) -> usize {
+        wrapper::approx_heap_bytes(self.raw)
+    }
+
+    /// Upper bound on the heap bytes this regex can hold however it is used: the
+    /// options' `max_mem`, which RE2 splits between the programs and their DFA caches,
+    /// plus the RE2 object and pattern.
+    pub fn max_heap_bytes(&self) -> usize {
+        wrapper::max_heap_bytes(self.raw)
+    }
+
+    /// The pattern this regex was compiled from, as written: without the rewrite
+    /// [`crate::Options::unicode_perl_classes`] or the ICU engine applied to it
+    pub fn pattern(&self) -> &str {
+        // The string lives in the shim's RE2 object, freed only when `self` drops
+        unsafe { wrapper::pattern(self.raw) }
+    }
+
     pub fn num_captures(&self) -> usize {
         wrapper::group_count(self.raw)
     }
 
     pub fn replace_one(&self, text: &str, rewrite: &str) -> Option<String> {
+        self.try_replace_one(text, rewrite).ok().flatten()
+    }
+
+    /// Like [`Regex::replace_one`], but surfaces failures inside RE2
+    pub fn try_replace_one(&self, text: &str, rewrite: &str) -> Result<Option<String>, Error> {
         wrapper::replace(self.raw, text, rewrite, true)
     }
 
+    /// Replace every match. `None` if nothing matched, or if `text` is longer than
+    /// [`Regex::MAX_REPLACE_ALL_LEN`] bytes.
     pub fn replace_all(&self, text: &str, rewrite: &str) -> Option<String> {
+        self.try_replace_all(text, rewrite).ok().flatten()
+    }
+
+    /// Like [`Regex::replace_all`], but surfaces failures inside RE2, and fails with
+    /// [`Error::TextTooLarge`] for a text over [`Regex::MAX_REPLACE_ALL_LEN`] bytes
+    pub fn try_replace_all(&self, text: &str, rewrite: &str) -> Result<Option<String>, Error> {
+        Self::check_replace_all_len(text.len())?;
         wrapper::replace(self.raw, text, rewrite, false)
     }
+
+    /// Whether [`Regex::replace_all`] accepts a text of `len` bytes, so that callers
+    /// with huge inputs can split them before building the text
+    pub fn check_replace_all_len(len: usize) -> Result<(), Error> {
+        if len > Self::MAX_REPLACE_ALL_LEN {
+            return Err(Error::TextTooLarge { len, max: Self::MAX_REPLACE_ALL_LEN });
+        }
+        Ok(())
+    }
+
+    /// Literal prefix every match must start with, and whether it matches
+    /// ASCII case-insensitively. `None` if the pattern has no fixed prefix, and always
+    /// with `system-re2`, which cannot reach RE2's parsed pattern.
+    pub fn required_prefix(&self) -> Option<(String, bool)> {
+        wrapper::required_prefix(self.raw)
+    }
+
+    /// Literal AND/OR query every match must satisfy, ignoring atoms shorter than
+    /// `min_atom_len` bytes. See [`Prefilter`]. Always [`Prefilter::All`] with `system-re2`.
+    pub fn prefilter(&self, min_atom_len: usize) -> Prefilter {
+        wrapper::prefilter(self.raw).prune(min_atom_len)
+    }
 }
 
 impl Drop for Regex {
     fn drop(&mut self) {
-        wrapper::delete_regex(self.raw)
+        wrapper::delete_regex(self.raw);
+        memory::untrack(self.counted_bytes);
     }
 }
 
@@ -0,0 +1,100 @@
+use crate::error::Error;
+use crate::wrapper::{self, Anchor, Options, RE2SetHandle};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Building,
+    Compiled,
+    // RE2 has no program to run after a failed compile; matching would crash.
+    Failed,
+}
+
+/// Multi-pattern matcher backed by `RE2::Set`
+///
+/// All patterns are compiled into a single program and matched in one DFA pass.
+/// Patterns are added with [`RegexSet::add`], then [`RegexSet::compile`] must be
+/// called once before matching.
+pub struct RegexSet {
+    raw: RE2SetHandle,
+    state: State,
+}
+
+impl RegexSet {
+    /// Create a set and add `patterns` to it. The set still needs to be compiled.
+    pub fn new<I, S>(patterns: I, opts: &Options, anchor: Anchor) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let raw = wrapper::set_new(Some(opts), anchor).ok_or(Error::OutOfMemory)?;
+        let mut set = Self { raw, state: State::Building };
+        for p in patterns {
+            set.add(p.as_ref())?;
+        }
+        Ok(set)
+    }
+
+    /// Add a pattern, returning its index in the output of [`RegexSet::matches`]
+    pub fn add(&mut self, pattern: &str) -> Result<usize, Error> {
+        if self.state != State::Building {
+            return Err(Error::AlreadyCompiled);
+        }
+        wrapper::set_add(self.raw, pattern)
+    }
+
+    /// Compile the set. Must be called exactly once, after all patterns are added.
+    pub fn compile(&mut self) -> Result<(), Error> {
+        if self.state != State::Building {
+            return Err(Error::AlreadyCompiled);
+        }
+        let result = wrapper::set_compile(self.raw);
+        self.state = if result.is_ok() { State::Compiled } else { State::Failed };
+        result
+    }
+
+    /// Number of patterns in the set
+    pub fn len(&self) -> usize {
+        wrapper::set_size(self.raw)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Indices of every pattern matching `text`, in ascending order.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::NotCompiled`] before a successful [`RegexSet::compile`]. A failed
+    /// search surfaces `RE2::Set::ErrorInfo`: [`Error::OutOfMemory`] when the DFA
+    /// ran out of memory, [`Error::Internal`] when RE2 reported an inconsistent state.
+    pub fn matches(&self, text: &str) -> Result<Vec<usize>, Error> {
+        self.check_compiled()?;
+        let mut out = wrapper::set_match(self.raw, text, Some(self.len()))?.unwrap_or_default();
+        out.sort_unstable();
+        Ok(out)
+    }
+
+    /// True if any pattern in the set matches `text`
+    ///
+    /// # Errors
+    ///
+    /// As for [`RegexSet::matches`].
+    pub fn is_match(&self, text: &str) -> Result<bool, Error> {
+        self.check_compiled()?;
+        Ok(wrapper::set_match(self.raw, text, None)?.is_some())
+    }
+
+    fn check_compiled(&self) -> Result<(), Error> {
+        match self.state {
+            State::Compiled => Ok(()),
+            State::Building | State::Failed => Err(Error::NotCompiled),
+        }
+    }
+}
+
+impl Drop for RegexSet {
+    fn drop(&mut self) {
+        wrapper::set_delete(self.raw)
+    }
+}
@@ -1,9 +1,16 @@
+use crate::ast::{Ast, ClassRange, ParseFlags};
+use crate::error::Error;
+use crate::hooks::{self, DfaCacheReset, DfaSearchFailure};
+use crate::logging::{self, LogRecord, Severity};
+use crate::prefilter::Prefilter;
 use re2_rs_sys::*;
 use std::{ffi::c_char, ptr, slice, str};
 
 /// Raw pointer type alias for readability
 pub type RE2WrapperHandle = *mut RE2Wrapper;
 pub type OptionsHandle = *mut RE2Options;
+pub type RE2SetHandle = *mut RE2SetWrapper;
+pub type RE2FilteredHandle = *mut RE2FilteredWrapper;
 
 /// Safe-ish wrapper around RE2 options (opaque to Rust)
 /// https://github.com/google/re2/blob/main/re2/re2.h#L678
@@ -35,6 +42,62 @@ impl Options {
         unsafe { re2_options_set_perl_classes(self.0, yes as i32); }
         self
     }
+
+    /// Give `\d`, `\s` and `\w` (and `\D`, `\S`, `\W`) Unicode meanings without ICU,
+    /// by rewriting them to RE2's own Unicode tables before parsing: `\d` becomes
+    /// `\p{Nd}`, so it matches `٣٤٥`; `\s` becomes White_Space (`\p{Z}` plus
+    /// `\t\n\x0B\f\r\x{85}`); `\w` becomes letters, marks, `Nd`, `Nl`, `Pc` and the
+    /// joiners U+200C/U+200D, close to UTS #18. Off by default.
+    ///
+    /// `\b` and `\B` fail with [`Error::Compile`]: RE2 tests word boundaries against a
+    /// fixed ASCII table rather than a class and has no lookaround, so they have no
+    /// Unicode form, and an ASCII one would disagree with `\w`. Errors and
+    /// [`crate::Regex::pattern`] show the pattern as written, not its rewrite.
+    ///
+    /// Has no effect under [`Options::posix_syntax`] unless [`Options::perl_classes`] is
+    /// on. With `system-re2`, patterns that need the classes as code point ranges (under
+    /// `posix_syntax`, or `\S` and `\W` inside `[...]`) fail with [`Error::Internal`].
+    pub fn unicode_perl_classes(self, yes: bool) -> Self {
+        unsafe { re2_options_set_unicode_perl_classes(self.0, yes as i32); }
+        self
+    }
+
+    /// Whether RE2 logs errors (e.g. a bad pattern) for this regex; on by default.
+    /// See [`crate::logging`] for where the messages go.
+    pub fn log_errors(self, yes: bool) -> Self {
+        unsafe { re2_options_set_log_errors(self.0, yes as i32); }
+        self
+    }
+
+    /// Approximate memory budget in bytes for the compiled program and its DFA caches;
+    /// 8 MiB by default. Patterns whose program does not fit fail to compile.
+    pub fn max_mem(self, bytes: i64) -> Self {
+        unsafe { re2_options_set_max_mem(self.0, bytes); }
+        self
+    }
+
+    /// Which build of RE2 parses the pattern; [`Engine::Plain`] by default
+    /// (the `re2-rs-icu` crate's options default to [`Engine::Icu`])
+    pub fn engine(self, engine: Engine) -> Self {
+        unsafe { re2_options_set_engine(self.0, engine.as_raw()); }
+        self
+    }
+
+    pub(crate) fn get_max_mem(&self) -> i64 {
+        unsafe { re2_options_max_mem(self.0) }
+    }
+}
+
+impl Clone for Options {
+    fn clone(&self) -> Self {
+        unsafe { Options(re2_options_clone(self.0)) }
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Drop for Options {
@@ -43,46 +106,113 @@ impl Drop for Options {
     }
 }
 
+/// Where a match must occur, mirrors `RE2::Anchor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    /// Match anywhere in the text
+    #[default]
+    Unanchored,
+    /// Match must start at the beginning of the text
+    Start,
+    /// Match must span the whole text
+    Both,
+}
+
+impl Anchor {
+    fn as_raw(self) -> i32 {
+        match self {
+            Anchor::Unanchored => 0,
+            Anchor::Start => 1,
+            Anchor::Both => 2,
+        }
+    }
+}
+
+/// Which build of RE2 a pattern is parsed by. Both can be used side by side; the choice
+/// is made per pattern and never depends on which crates are in the build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Engine {
+    /// RE2's built-in Unicode tables: scripts and general categories for `\p{..}`
+    #[default]
+    Plain,
+    /// ICU's property data for `\p{..}`, e.g. `\p{Emoji}` or `\p{Alphabetic}`. Needs the
+    /// `icu` feature, see [`has_icu`]; without it such patterns fail to compile.
+    ///
+    /// The ICU build of RE2 only parses: the pattern is printed back from its parse tree and
+    /// compiled by the plain build, so RE2 (and [`crate::hooks`]) see that printed form.
+    Icu,
+}
+
+impl Engine {
+    fn as_raw(self) -> i32 {
+        match self {
+            Engine::Plain => RE2_ENGINE_PLAIN as i32,
+            Engine::Icu => RE2_ENGINE_ICU as i32,
+        }
+    }
+}
+
+/// Error the shim recorded if the last call into it on this thread caught a C++
+/// exception. Must be checked before any other shim call, which resets it.
+fn shim_error() -> Option<Error> {
+    match unsafe { re2_last_status() } as u32 {
+        RE2_STATUS_OK => None,
+        RE2_STATUS_OUT_OF_MEMORY => Some(Error::OutOfMemory),
+        _ => {
+            let mut p: *const c_char = ptr::null();
+            let mut len: usize = 0;
+            unsafe { re2_last_status_message(&mut p, &mut len) };
+            let msg = if p.is_null() {
+                String::new()
+            } else {
+                String::from_utf8_lossy(unsafe { slice::from_raw_parts(p as *const u8, len) }).into_owned()
+            };
+            Some(Error::Internal(msg))
+        }
+    }
+}
+
+/// `RE2::ErrorCode` for a program that did not fit in `max_mem`
+pub const ERROR_PATTERN_TOO_LARGE: i32 = 15;
+
 /// Unified constructor that works with or without ICU
-pub fn compile_regex(pattern: &str, opts: Option<&Options>) -> Result<RE2WrapperHandle, String> {
+pub fn compile_regex(pattern: &str, opts: Option<&Options>) -> Result<RE2WrapperHandle, Error> {
+    compile_regex_with_code(pattern, opts).map_err(|(err, _)| err)
+}
+
+/// As [`compile_regex`], also returning RE2's error code when it rejected the pattern
+/// (0 if the failure was in the shim)
+pub fn compile_regex_with_code(pattern: &str, opts: Option<&Options>) -> Result<RE2WrapperHandle, (Error, i32)> {
     let cpat = pattern.as_bytes();
     let mut err_ptr: *const c_char = ptr::null();
     let mut err_len: usize = 0;
 
     let raw = unsafe {
-        #[cfg(feature = "icu")]
-        {
-            let opt_ptr = opts.map(|o| o.0).unwrap_or(ptr::null_mut());
-            re2_new_with_options(
+        match opts {
+            Some(o) => re2_new_with_options(
                 cpat.as_ptr() as *const i8,
                 cpat.len(),
-                opt_ptr,
+                o.0,
                 &mut err_ptr,
                 &mut err_len,
-            )
-        }
-        #[cfg(not(feature = "icu"))]
-        {
-            re2_new(
+            ),
+            None => re2_new(
                 cpat.as_ptr() as *const i8,
                 cpat.len(),
                 &mut err_ptr,
                 &mut err_len,
-            )
+            ),
         }
     };
 
-    if raw.is_null() || err_len != 0 || unsafe { re2_ok(raw) } != 1 {
-        let msg = if !err_ptr.is_null() && err_len > 0 {
-            unsafe { str::from_utf8_unchecked(slice::from_raw_parts(err_ptr as *const u8, err_len)) }
-                .to_string()
-        } else {
-            "RE2 compile error".to_string()
-        };
-        if !raw.is_null() {
-            unsafe { re2_delete(raw) }
-        }
-        return Err(msg);
+    if raw.is_null() {
+        return Err((shim_error().unwrap_or(Error::OutOfMemory), 0));
+    }
+    if err_len != 0 || unsafe { re2_ok(raw) } != 1 {
+        let err = compile_error(err_ptr, err_len);
+        let code = unsafe { re2_error_code(raw) };
+        unsafe { re2_delete(raw) }
+        return Err((err, code));
     }
     Ok(raw)
 }
@@ -91,15 +221,23 @@ pub fn delete_regex(raw: RE2WrapperHandle) {
     unsafe { re2_delete(raw) }
 }
 
-pub fn full_match(raw: RE2WrapperHandle, text: &str) -> bool {
-    unsafe { re2_full_match(raw, text.as_ptr() as *const c_char, text.len()) == 1 }
+/// `value`, unless the shim call that produced it recorded an error
+fn shim_result<T>(value: T) -> Result<T, Error> {
+    match shim_error() {
+        Some(err) => Err(err),
+        None => Ok(value),
+    }
+}
+
+pub fn full_match(raw: RE2WrapperHandle, text: &str) -> Result<bool, Error> {
+    shim_result(unsafe { re2_full_match(raw, text.as_ptr() as *const c_char, text.len()) } == 1)
 }
 
-pub fn partial_match(raw: RE2WrapperHandle, text: &str) -> bool {
-    unsafe { re2_partial_match(raw, text.as_ptr() as *const c_char, text.len()) == 1 }
+pub fn partial_match(raw: RE2WrapperHandle, text: &str) -> Result<bool, Error> {
+    shim_result(unsafe { re2_partial_match(raw, text.as_ptr() as *const c_char, text.len()) } == 1)
 }
 
-pub fn captures(raw: RE2WrapperHandle, text: &str, full: bool) -> Option<Vec<Option<&str>>> {
+pub fn captures(raw: RE2WrapperHandle, text: &str, full: bool) -> Result<Option<Vec<Option<&str>>>, Error> {
     let cap_count = 1 + group_count(raw);
     let mut spans = vec![re2_span_t { start: usize::MAX, len: 0 }; cap_count];
     let mut written: usize = 0;
@@ -124,46 +262,533 @@ pub fn captures(raw: RE2WrapperHandle, text: &str, full: bool) -> Option<Vec<Opt
             )
         }
     } == 1;
-    if !ok { return None; }
+    if !ok {
+        return shim_result(None);
+    }
     let written = written.min(spans.len());
     let mut out = Vec::with_capacity(written);
-    for i in 0..written {
-        let s = spans[i];
+    for s in &spans[..written] {
         if s.start == usize::MAX {
             out.push(None);
         } else {
             out.push(Some(&text[s.start..s.start + s.len]));
         }
     }
-    Some(out)
+    Ok(Some(out))
+}
+
+/// The pattern `raw` was compiled from, as written
+///
+/// # Safety
+/// `raw` must be live, and outlive the returned string.
+pub unsafe fn pattern<'a>(raw: RE2WrapperHandle) -> &'a str {
+    let mut p: *const c_char = ptr::null();
+    let mut len: usize = 0;
+    unsafe { re2_pattern(raw, &mut p, &mut len) };
+    if p.is_null() {
+        return "";
+    }
+    // Copied from the &str the regex was compiled from
+    unsafe { std::str::from_utf8_unchecked(slice::from_raw_parts(p as *const u8, len)) }
 }
 
 pub fn group_count(raw: RE2WrapperHandle) -> usize {
     unsafe { re2_group_count(raw) as usize }
 }
 
-pub fn replace(raw: RE2WrapperHandle, text: &str, rewrite: &str, one: bool) -> Option<String> {
-    const MAX: usize = 1 << 20;
-    let mut buf = vec![0u8; MAX];
+pub fn program_size(raw: RE2WrapperHandle) -> usize {
+    unsafe { re2_program_size(raw).max(0) as usize }
+}
+
+pub fn max_repeat(raw: RE2WrapperHandle) -> Result<u32, Error> {
+    shim_result(unsafe { re2_max_repeat(raw) }.max(0) as u32)
+}
+
+pub fn approx_heap_bytes(raw: RE2WrapperHandle) -> usize {
+    unsafe { re2_approx_heap_bytes(raw) }
+}
+
+pub fn max_heap_bytes(raw: RE2WrapperHandle) -> usize {
+    unsafe { re2_max_heap_bytes(raw) }
+}
+
+pub fn replace(raw: RE2WrapperHandle, text: &str, rewrite: &str, one: bool) -> Result<Option<String>, Error> {
+    // Room for the text plus some growth; a larger result is retried at its exact size.
+    let mut buf = vec![0u8; text.len() + text.len() / 4 + rewrite.len() + 1];
+    loop {
+        let mut written: usize = 0;
+        let ok = unsafe {
+            if one {
+                re2_replace_one(raw, text.as_ptr() as *const i8, text.len(),
+                                rewrite.as_ptr() as *const i8, rewrite.len(),
+                                buf.as_mut_ptr() as *mut i8, buf.len(), &mut written)
+            } else {
+                re2_replace_all(raw, text.as_ptr() as *const i8, text.len(),
+                                rewrite.as_ptr() as *const i8, rewrite.len(),
+                                buf.as_mut_ptr() as *mut i8, buf.len(), &mut written)
+            }
+        };
+        if ok == 1 {
+            return Ok(Some(String::from_utf8_lossy(&buf[..written]).into_owned()));
+        }
+        if let Some(err) = shim_error() {
+            return Err(err);
+        }
+        if written < buf.len() {
+            return Ok(None);
+        }
+        buf = vec![0u8; written + 1];
+    }
+}
+
+/// Whether [`Engine::Icu`] is built in
+pub fn has_icu() -> bool {
+    unsafe { re2_has_icu() == 1 }
+}
+
+pub fn abseil_version() -> String {
+    take_string(unsafe { re2_abseil_version() }).unwrap_or_default()
+}
+
+pub fn icu_version() -> Option<String> {
+    take_string(unsafe { re2_icu_version() })
+}
+
+pub fn icu_unicode_version() -> Option<String> {
+    take_string(unsafe { re2_icu_unicode_version() })
+}
+
+/// Build an [`Error::Compile`] from a shim-owned error message, or the shim's
+/// exception status if it produced none
+fn compile_error(err_ptr: *const c_char, err_len: usize) -> Error {
+    if err_ptr.is_null()
+        && let Some(err) = shim_error()
+    {
+        return err;
+    }
+    let msg = if !err_ptr.is_null() && err_len > 0 {
+        unsafe { str::from_utf8_unchecked(slice::from_raw_parts(err_ptr as *const u8, err_len)) }
+            .to_string()
+    } else {
+        "RE2 compile error".to_string()
+    };
+    Error::Compile(msg)
+}
+
+pub fn set_new(opts: Option<&Options>, anchor: Anchor) -> Option<RE2SetHandle> {
+    let opt_ptr = opts.map(|o| o.0 as *const RE2Options).unwrap_or(ptr::null());
+    let raw = unsafe { re2_set_new(opt_ptr, anchor.as_raw()) };
+    (!raw.is_null()).then_some(raw)
+}
+
+pub fn set_delete(raw: RE2SetHandle) {
+    unsafe { re2_set_delete(raw) }
+}
+
+pub fn set_add(raw: RE2SetHandle, pattern: &str) -> Result<usize, Error> {
+    let mut err_ptr: *const c_char = ptr::null();
+    let mut err_len: usize = 0;
+    let idx = unsafe {
+        re2_set_add(raw, pattern.as_ptr() as *const c_char, pattern.len(), &mut err_ptr, &mut err_len)
+    };
+    if idx < 0 {
+        return Err(compile_error(err_ptr, err_len));
+    }
+    Ok(idx as usize)
+}
+
+pub fn set_size(raw: RE2SetHandle) -> usize {
+    unsafe { re2_set_size(raw) as usize }
+}
+
+/// RE2 reports no reason for a failed compile other than the program not fitting in `max_mem`
+pub fn set_compile(raw: RE2SetHandle) -> Result<(), Error> {
+    if unsafe { re2_set_compile(raw) } == 1 {
+        return Ok(());
+    }
+    Err(shim_error().unwrap_or(Error::OutOfMemory))
+}
+
+/// `Ok(None)` if nothing matched. With `cap == None` only a match/no-match answer
+/// is computed, and a match yields an empty index list.
+pub fn set_match(raw: RE2SetHandle, text: &str, cap: Option<usize>) -> Result<Option<Vec<usize>>, Error> {
+    let mut indices = vec![0i32; cap.unwrap_or(0)];
+    let out_ptr = if cap.is_some() { indices.as_mut_ptr() } else { ptr::null_mut() };
     let mut written: usize = 0;
+    let mut kind: i32 = 0;
     let ok = unsafe {
-        if one {
-            re2_replace_one(raw, text.as_ptr() as *const i8, text.len(),
-                            rewrite.as_ptr() as *const i8, rewrite.len(),
-                            buf.as_mut_ptr() as *mut i8, buf.len(), &mut written)
+        re2_set_match(raw, text.as_ptr() as *const c_char, text.len(),
+                      out_ptr, indices.len(), &mut written, &mut kind)
+    } == 1;
+    match kind {
+        0 => {
+            if let Some(err) = shim_error() {
+                return Err(err);
+            }
+        }
+        1 => return Err(Error::NotCompiled),
+        2 => return Err(Error::OutOfMemory),
+        _ => return Err(Error::Internal("RE2::Set reported an inconsistent state".into())),
+    }
+    if !ok {
+        return Ok(None);
+    }
+    indices.truncate(written);
+    Ok(Some(indices.into_iter().map(|i| i as usize).collect()))
+}
+
+pub fn filtered_new(min_atom_len: usize) -> Option<RE2FilteredHandle> {
+    let raw = unsafe { re2_filtered_new(min_atom_len.min(i32::MAX as usize) as i32) };
+    (!raw.is_null()).then_some(raw)
+}
+
+pub fn filtered_delete(raw: RE2FilteredHandle) {
+    unsafe { re2_filtered_delete(raw) }
+}
+
+pub fn filtered_add(raw: RE2FilteredHandle, pattern: &str, opts: &Options) -> Result<usize, Error> {
+    let mut err_ptr: *const c_char = ptr::null();
+    let mut err_len: usize = 0;
+    let id = unsafe {
+        re2_filtered_add(raw, pattern.as_ptr() as *const c_char, pattern.len(), opts.0,
+                         &mut err_ptr, &mut err_len)
+    };
+    if id < 0 {
+        return Err(compile_error(err_ptr, err_len));
+    }
+    Ok(id as usize)
+}
+
+pub fn filtered_size(raw: RE2FilteredHandle) -> usize {
+    unsafe { re2_filtered_size(raw) as usize }
+}
+
+/// Compile and copy out the atoms. Atoms are lowercased by RE2.
+pub fn filtered_compile(raw: RE2FilteredHandle) -> Result<Vec<String>, Error> {
+    unsafe {
+        re2_filtered_compile(raw);
+        if let Some(err) = shim_error() {
+            return Err(err);
+        }
+        let n = re2_filtered_num_atoms(raw);
+        Ok((0..n)
+            .map(|i| {
+                let mut p: *const c_char = ptr::null();
+                let mut len: usize = 0;
+                re2_filtered_atom(raw, i, &mut p, &mut len);
+                if p.is_null() {
+                    return String::new();
+                }
+                String::from_utf8_lossy(slice::from_raw_parts(p as *const u8, len)).into_owned()
+            })
+            .collect())
+    }
+}
+
+pub fn filtered_slow_first_match(raw: RE2FilteredHandle, text: &str) -> Result<Option<usize>, Error> {
+    let id = unsafe { re2_filtered_slow_first_match(raw, text.as_ptr() as *const c_char, text.len()) };
+    shim_result((id >= 0).then_some(id as usize))
+}
+
+pub fn filtered_first_match(raw: RE2FilteredHandle, text: &str, atoms: &[i32]) -> Result<Option<usize>, Error> {
+    let id = unsafe {
+        re2_filtered_first_match(raw, text.as_ptr() as *const c_char, text.len(),
+                                 atoms.as_ptr(), atoms.len())
+    };
+    if let Some(err) = shim_error() {
+        return Err(err);
+    }
+    Ok((id >= 0).then_some(id as usize))
+}
+
+pub fn filtered_all_matches(raw: RE2FilteredHandle, text: &str, atoms: &[i32]) -> Result<Vec<usize>, Error> {
+    let mut ids = vec![0i32; filtered_size(raw)];
+    let mut written: usize = 0;
+    unsafe {
+        re2_filtered_all_matches(raw, text.as_ptr() as *const c_char, text.len(),
+                                 atoms.as_ptr(), atoms.len(),
+                                 ids.as_mut_ptr(), ids.len(), &mut written);
+    }
+    if let Some(err) = shim_error() {
+        return Err(err);
+    }
+    Ok(ids[..written].iter().map(|&i| i as usize).collect())
+}
+
+pub fn filtered_all_potentials(raw: RE2FilteredHandle, atoms: &[i32]) -> Result<Vec<usize>, Error> {
+    let mut ids = vec![0i32; filtered_size(raw)];
+    let mut written: usize = 0;
+    unsafe {
+        re2_filtered_all_potentials(raw, atoms.as_ptr(), atoms.len(),
+                                    ids.as_mut_ptr(), ids.len(), &mut written);
+    }
+    if let Some(err) = shim_error() {
+        return Err(err);
+    }
+    Ok(ids[..written].iter().map(|&i| i as usize).collect())
+}
+
+/// Copy RE2's prefilter for `raw` into a Rust tree
+pub fn prefilter(raw: RE2WrapperHandle) -> Prefilter {
+    unsafe fn convert(node: *const RE2Prefilter) -> Prefilter {
+        unsafe {
+            match re2_prefilter_op(node) {
+                0 => Prefilter::All,
+                1 => Prefilter::None,
+                2 => {
+                    let mut p: *const c_char = ptr::null();
+                    let mut len: usize = 0;
+                    re2_prefilter_atom(node, &mut p, &mut len);
+                    if p.is_null() {
+                        return Prefilter::Atom(String::new());
+                    }
+                    Prefilter::Atom(String::from_utf8_lossy(slice::from_raw_parts(p as *const u8, len)).into_owned())
+                }
+                op => {
+                    let subs = (0..re2_prefilter_num_subs(node))
+                        .map(|i| convert(re2_prefilter_sub(node, i)))
+                        .collect();
+                    if op == 3 { Prefilter::And(subs) } else { Prefilter::Or(subs) }
+                }
+            }
+        }
+    }
+
+    unsafe {
+        let root = re2_prefilter_from_re2(raw);
+        if root.is_null() {
+            return Prefilter::All;
+        }
+        let tree = convert(root);
+        re2_prefilter_delete(root);
+        tree
+    }
+}
+
+/// Copy and free a shim-owned string
+pub fn take_string(raw: *mut RE2String) -> Option<String> {
+    if raw.is_null() {
+        return None;
+    }
+    unsafe {
+        let data = re2_string_data(raw);
+        let len = re2_string_len(raw);
+        let out = if data.is_null() {
+            String::new()
+        } else {
+            String::from_utf8_lossy(slice::from_raw_parts(data as *const u8, len)).into_owned()
+        };
+        re2_string_delete(raw);
+        Some(out)
+    }
+}
+
+/// Error for a failed parse: the shim's exception status, else RE2's message in `err`
+fn parse_error(err: *mut RE2String) -> Error {
+    if let Some(shim) = shim_error() {
+        take_string(err);
+        return shim;
+    }
+    Error::Compile(take_string(err).unwrap_or_else(|| "RE2 parse error".to_string()))
+}
+
+/// Parse with `Regexp::Parse` and copy the tree into an [`Ast`]
+pub fn regexp_parse(pattern: &str, flags: ParseFlags) -> Result<Ast, Error> {
+    let mut err: *mut RE2String = ptr::null_mut();
+    let root = unsafe {
+        re2_regexp_parse(pattern.as_ptr() as *const c_char, pattern.len(), flags.bits() as i32, &mut err)
+    };
+    if root.is_null() {
+        return Err(parse_error(err));
+    }
+    let ast = unsafe { regexp_to_ast(root) };
+    unsafe { re2_regexp_delete(root) };
+    Ok(ast)
+}
+
+pub fn regexp_mimics_pcre(pattern: &str, flags: ParseFlags) -> Result<bool, Error> {
+    let mut err: *mut RE2String = ptr::null_mut();
+    let root = unsafe {
+        re2_regexp_parse(pattern.as_ptr() as *const c_char, pattern.len(), flags.bits() as i32, &mut err)
+    };
+    if root.is_null() {
+        return Err(parse_error(err));
+    }
+    let mimics = unsafe { re2_regexp_mimics_pcre(root) } == 1;
+    unsafe { re2_regexp_delete(root) };
+    Ok(mimics)
+}
+
+fn rune_to_char(r: i32) -> char {
+    char::from_u32(r as u32).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+unsafe fn regexp_to_ast(node: *const RE2Regexp) -> Ast {
+    unsafe {
+        let flags = ParseFlags::from_bits_truncate(re2_regexp_flags(node) as u32);
+        let fold_case = flags.contains(ParseFlags::FOLD_CASE);
+        let greedy = !flags.contains(ParseFlags::NON_GREEDY);
+        let subs = || -> Vec<Ast> {
+            (0..re2_regexp_nsub(node)).map(|i| regexp_to_ast(re2_regexp_sub(node, i))).collect()
+        };
+        let sub = || Box::new(regexp_to_ast(re2_regexp_sub(node, 0)));
+
+        match re2_regexp_op(node) {
+            1 => Ast::NoMatch,
+            2 => Ast::EmptyMatch,
+            3 => Ast::Literal { c: rune_to_char(re2_regexp_rune(node)), fold_case },
+            4 => {
+                let mut runes: *const i32 = ptr::null();
+                let n = re2_regexp_runes(node, &mut runes);
+                let s = if runes.is_null() {
+                    String::new()
+                } else {
+                    slice::from_raw_parts(runes, n).iter().map(|&r| rune_to_char(r)).collect()
+                };
+                Ast::LiteralString { s, fold_case }
+            }
+            5 => Ast::Concat(subs()),
+            6 => Ast::Alternate(subs()),
+            7 => Ast::Star { sub: sub(), greedy },
+            8 => Ast::Plus { sub: sub(), greedy },
+            9 => Ast::Quest { sub: sub(), greedy },
+            10 => {
+                let max = re2_regexp_max(node);
+                Ast::Repeat {
+                    sub: sub(),
+                    min: re2_regexp_min(node) as u32,
+                    max: (max >= 0).then_some(max as u32),
+                    greedy,
+                }
+            }
+            11 => {
+                let mut p: *const c_char = ptr::null();
+                let mut len: usize = 0;
+                re2_regexp_name(node, &mut p, &mut len);
+                let name = (!p.is_null())
+                    .then(|| String::from_utf8_lossy(slice::from_raw_parts(p as *const u8, len)).into_owned());
+                Ast::Capture { sub: sub(), index: re2_regexp_cap(node) as usize, name }
+            }
+            12 => Ast::AnyChar,
+            13 => Ast::AnyByte,
+            14 => Ast::BeginLine,
+            15 => Ast::EndLine,
+            16 => Ast::WordBoundary,
+            17 => Ast::NoWordBoundary,
+            18 => Ast::BeginText,
+            19 => Ast::EndText { was_dollar: flags.contains(ParseFlags::WAS_DOLLAR) },
+            20 => {
+                let ranges = (0..re2_regexp_num_ranges(node))
+                    .map(|i| {
+                        let (mut lo, mut hi) = (0i32, 0i32);
+                        re2_regexp_range(node, i, &mut lo, &mut hi);
+                        ClassRange { lo: lo as u32, hi: hi as u32 }
+                    })
+                    .collect();
+                Ast::CharClass(ranges)
+            }
+            _ => Ast::HaveMatch(re2_regexp_match_id(node) as usize),
+        }
+    }
+}
+
+/// Canonical (`simplify == false`) or simplified form of `pattern`
+pub fn regexp_to_string(pattern: &str, flags: ParseFlags, simplify: bool) -> Result<String, Error> {
+    let mut err: *mut RE2String = ptr::null_mut();
+    let out = unsafe {
+        let p = pattern.as_ptr() as *const c_char;
+        let f = flags.bits() as i32;
+        if simplify {
+            re2_regexp_simplify(p, pattern.len(), f, &mut err)
         } else {
-            re2_replace_all(raw, text.as_ptr() as *const i8, text.len(),
-                            rewrite.as_ptr() as *const i8, rewrite.len(),
-                            buf.as_mut_ptr() as *mut i8, buf.len(), &mut written)
+            re2_regexp_canonical(p, pattern.len(), f, &mut err)
         }
     };
-    if ok == 1 {
-        Some(String::from_utf8_lossy(&buf[..written]).into_owned())
-    } else {
-        None
+    if out.is_null() {
+        return Err(parse_error(err));
     }
+    Ok(take_string(out).unwrap_or_default())
 }
 
-pub fn has_icu() -> bool {
-    unsafe { re2_has_icu() == 1 }
+pub fn required_prefix(raw: RE2WrapperHandle) -> Option<(String, bool)> {
+    let mut foldcase: i32 = 0;
+    let prefix = take_string(unsafe { re2_required_prefix(raw, &mut foldcase) })?;
+    Some((prefix, foldcase == 1))
+}
+
+pub fn prog_dump(raw: RE2WrapperHandle) -> Option<String> {
+    take_string(unsafe { re2_prog_dump(raw) })
+}
+
+pub fn prog_dump_unanchored(raw: RE2WrapperHandle) -> Option<String> {
+    take_string(unsafe { re2_prog_dump_unanchored(raw) })
+}
+
+pub fn prog_dump_byte_map(raw: RE2WrapperHandle) -> Option<String> {
+    take_string(unsafe { re2_prog_dump_byte_map(raw) })
+}
+
+pub fn prog_dot(raw: RE2WrapperHandle) -> Option<String> {
+    take_string(unsafe { re2_prog_dot(raw) })
+}
+
+unsafe fn hook_pattern(pattern: *const c_char, pattern_len: usize) -> Option<String> {
+    if pattern.is_null() {
+        return None;
+    }
+    let bytes = unsafe { slice::from_raw_parts(pattern as *const u8, pattern_len) };
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+unsafe extern "C" fn dfa_state_cache_reset_trampoline(
+    state_budget: i64,
+    state_cache_size: usize,
+    pattern: *const c_char,
+    pattern_len: usize,
+) {
+    let pattern = unsafe { hook_pattern(pattern, pattern_len) };
+    hooks::dispatch_dfa_cache_reset(&DfaCacheReset { state_budget, state_cache_size, pattern });
+}
+
+unsafe extern "C" fn dfa_search_failure_trampoline(pattern: *const c_char, pattern_len: usize) {
+    let pattern = unsafe { hook_pattern(pattern, pattern_len) };
+    hooks::dispatch_dfa_search_failure(&DfaSearchFailure { pattern });
+}
+
+pub fn set_dfa_cache_reset_hook(enabled: bool) {
+    let cb: RE2DFAStateCacheResetCallback = enabled.then_some(dfa_state_cache_reset_trampoline);
+    unsafe { re2_set_dfa_state_cache_reset_hook(cb) }
+}
+
+pub fn set_dfa_search_failure_hook(enabled: bool) {
+    let cb: RE2DFASearchFailureCallback = enabled.then_some(dfa_search_failure_trampoline);
+    unsafe { re2_set_dfa_search_failure_hook(cb) }
+}
+
+unsafe extern "C" fn log_trampoline(
+    severity: i32,
+    file: *const c_char,
+    file_len: usize,
+    line: i32,
+    msg: *const c_char,
+    msg_len: usize,
+) {
+    let (file, message) = unsafe {
+        (
+            String::from_utf8_lossy(slice::from_raw_parts(file as *const u8, file_len)),
+            String::from_utf8_lossy(slice::from_raw_parts(msg as *const u8, msg_len)),
+        )
+    };
+    let severity = match severity {
+        0 => Severity::Info,
+        1 => Severity::Warning,
+        2 => Severity::Error,
+        _ => Severity::Fatal,
+    };
+    logging::dispatch(&LogRecord { severity, file: &file, line: line.max(0) as u32, message: &message });
+}
+
+pub fn set_log_callback(enabled: bool) {
+    let cb: RE2LogCallback = enabled.then_some(log_trampoline);
+    unsafe { re2_set_log_callback(cb) }
 }
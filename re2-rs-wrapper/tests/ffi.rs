@@ -35,4 +35,37 @@ fn ffi_unsupported_lookbehind() {
         assert_eq!(re2_ok(re), 0);
         re2_delete(re);
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn ffi_exceptions_become_status_codes() {
+    use re2_rs_sys::{
+        re2_last_status, re2_last_status_message, re2_replace_one, RE2_STATUS_INTERNAL, RE2_STATUS_OK,
+        RE2_STATUS_OUT_OF_MEMORY,
+    };
+    unsafe {
+        let pat = CString::new("a").unwrap();
+        let re = re2_new(pat.as_ptr(), pat.as_bytes().len(), std::ptr::null_mut(), std::ptr::null_mut());
+        let mut buf = [0 as std::os::raw::c_char; 16];
+        let mut written = 0usize;
+
+        // std::string refuses the length before touching the text: std::length_error
+        let ok = re2_replace_one(re, pat.as_ptr(), usize::MAX, pat.as_ptr(), 1, buf.as_mut_ptr(), buf.len(), &mut written);
+        assert_eq!(ok, 0);
+        assert_eq!(re2_last_status() as u32, RE2_STATUS_INTERNAL);
+        let mut msg = std::ptr::null();
+        let mut msg_len = 0usize;
+        re2_last_status_message(&mut msg, &mut msg_len);
+        assert!(msg_len > 0);
+
+        // Allocation fails before the text is read: std::bad_alloc
+        let ok = re2_replace_one(re, pat.as_ptr(), 1 << 61, pat.as_ptr(), 1, buf.as_mut_ptr(), buf.len(), &mut written);
+        assert_eq!(ok, 0);
+        assert_eq!(re2_last_status() as u32, RE2_STATUS_OUT_OF_MEMORY);
+
+        // The next call resets the status
+        assert_eq!(re2_full_match(re, pat.as_ptr(), 1), 1);
+        assert_eq!(re2_last_status() as u32, RE2_STATUS_OK);
+        re2_delete(re);
+    }
+}
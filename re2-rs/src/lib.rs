@@ -1 +1,7 @@
-pub use re2_rs_wrapper::*;
\ No newline at end of file
+//! RE2 for Rust. See the README for the features and how RE2 is built.
+//!
+//! [`Regex::new`] and [`Regex::with_options`] return [`Error`], not `String` as in
+//! earlier versions: RE2's message is in [`Error::Compile`], and [`Error`]'s `Display`
+//! prints it with a "RE2 compile error: " prefix.
+
+pub use re2_rs_wrapper::*;
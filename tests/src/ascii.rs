@@ -1,4 +1,14 @@
-use re2_rs_wrapper::{Regex};
+use re2_rs_wrapper::{Anchor, Error, FilteredRegexSet, Options, Regex, RegexSet};
+
+// Every expectation runs in both modes: RE2's ASCII Perl classes (the default) and
+// Options::unicode_perl_classes, which only changes \d, \s and \w (and rejects \b).
+fn modes() -> [(bool, Options); 2] {
+    [(false, Options::new()), (true, Options::new().unicode_perl_classes(true))]
+}
+
+fn regex(pattern: &str, opts: &Options) -> Regex {
+    Regex::with_options(pattern, opts).unwrap()
+}
 
 #[test]
 fn ascii_digit_matching_only() {
@@ -21,9 +31,11 @@ fn ascii_word_boundaries() {
 
 #[test]
 fn no_unicode_digit_support() {
-    let re = Regex::new(r"^\d+$").unwrap();
-    assert!(re.full_match("123"));    // ASCII works
-    assert!(!re.full_match("٣٤٥"));  // Arabic-Indic digits should fail
+    for (unicode, opts) in modes() {
+        let re = regex(r"^\d+$", &opts);
+        assert!(re.full_match("123")); // ASCII works
+        assert_eq!(re.full_match("٣٤٥"), unicode); // Arabic-Indic digits only with Unicode tables
+    }
 }
 
 #[test]
@@ -34,8 +46,85 @@ fn no_unicode_word_boundaries() {
 
 #[test]
 fn no_unicode_case_folding() {
-    let re = Regex::new(r"^straße$").unwrap();
-    assert!(re.full_match("straße"));
-    assert!(!re.full_match("STRASSE")); // ß→SS folding missing
+    for (unicode, opts) in modes() {
+        let re = regex(r"^straße$", &opts);
+        assert!(re.full_match("straße"), "unicode={}", unicode);
+        assert!(!re.full_match("STRASSE"), "unicode={}", unicode); // ß→SS folding missing
+    }
+}
+
+#[test]
+fn unicode_perl_classes_word_and_space() {
+    for (unicode, opts) in modes() {
+        assert!(regex(r"^\w+$", &opts).full_match("word_42"));
+        assert_eq!(regex(r"^\w+$", &opts).full_match("κόσμος"), unicode);
+        assert_eq!(regex(r"^\w+$", &opts).full_match("नमस्ते"), unicode); // combining marks
+        assert!(regex(r"^\s+$", &opts).full_match(" \t\r\n"));
+        assert_eq!(regex(r"^\s+$", &opts).full_match("\u{a0}\u{3000}\u{2028}"), unicode);
+        assert_eq!(regex(r"^\W+$", &opts).full_match("κόσμος"), !unicode);
+        assert_eq!(regex(r"^\S+$", &opts).full_match("\u{3000}"), !unicode);
+        assert_eq!(regex(r"^\D+$", &opts).full_match("٣"), !unicode);
+    }
+}
+
+#[test]
+#[cfg_attr(feature = "system-re2", ignore = "needs the vendored RE2")]
+fn unicode_perl_classes_inside_brackets() {
+    let opts = Options::new().unicode_perl_classes(true);
+    assert!(regex(r"^[\d.]+$", &opts).full_match("٣.٤"));
+    assert!(regex(r"^[^\w]+$", &opts).full_match("!? "));
+    assert!(!regex(r"^[^\w]+$", &opts).full_match("é"));
+    // Negated classes inside brackets become explicit ranges
+    assert!(regex(r"^[\W\d]+$", &opts).full_match("!٣ "));
+    assert!(!regex(r"^[\W\d]+$", &opts).full_match("é"));
+    assert!(regex(r"^[\S]+$", &opts).full_match("κ"));
+    assert!(!regex(r"^[\S]+$", &opts).full_match("\u{3000}"));
+    assert!(regex(r"^[[:alpha:]\s]+$", &opts).full_match("ab\u{3000}"));
+    assert!(regex(r"^[]\d]+$", &opts).full_match("]٣"));
 }
 
+#[test]
+#[cfg_attr(feature = "system-re2", ignore = "needs the vendored RE2")]
+fn unicode_perl_classes_leave_escapes_alone() {
+    let opts = Options::new().unicode_perl_classes(true);
+    assert!(regex(r"^\\d$", &opts).full_match(r"\d"));
+    assert!(regex(r"^\Q\d\w\E\d$", &opts).full_match(r"\d\w٣"));
+    assert!(regex(r"^\p{Greek}\d$", &opts).full_match("κ٣"));
+    // Under POSIX syntax \d is not a Perl class, so it stays an error
+    assert!(Regex::with_options(r"\d", &opts.clone().posix_syntax(true)).is_err());
+    assert!(regex(r"^\d$", &opts.posix_syntax(true).perl_classes(true)).full_match("٣"));
+}
+
+#[test]
+fn unicode_perl_classes_in_sets() {
+    for (unicode, opts) in modes() {
+        let mut set = RegexSet::new([r"\d+", r"\s"], &opts, Anchor::Both).unwrap();
+        set.compile().unwrap();
+        assert_eq!(set.matches("123").unwrap(), vec![0]);
+        assert_eq!(set.matches("٣٤٥").unwrap(), if unicode { vec![0] } else { vec![] });
+    }
+}
+
+#[test]
+fn unicode_perl_classes_reject_word_boundaries() {
+    let opts = Options::new().log_errors(false).unicode_perl_classes(true);
+    for pattern in [r"\bword\b", r"a\Bb"] {
+        let err = Regex::with_options(pattern, &opts).err().unwrap();
+        assert!(matches!(&err, Error::Compile(msg) if msg.contains("word boundary")), "{:?}", err);
+    }
+    // Escaped or quoted they are no boundaries
+    assert!(regex(r"^\\b\Q\B\E$", &opts).full_match(r"\b\B"));
+}
+
+#[test]
+fn unicode_perl_classes_keep_the_pattern_as_written() {
+    let opts = Options::new().log_errors(false).unicode_perl_classes(true);
+    assert_eq!(regex(r"\d+\s", &opts).pattern(), r"\d+\s");
+
+    let missing_paren = Error::Compile(r"missing ): \d(".to_string());
+    assert_eq!(Regex::with_options(r"\d(", &opts).err(), Some(missing_paren.clone()));
+    let mut set = RegexSet::new([r"\w"], &opts, Anchor::Unanchored).unwrap();
+    assert_eq!(set.add(r"\d("), Err(missing_paren.clone()));
+    let mut filtered = FilteredRegexSet::new(1).unwrap();
+    assert_eq!(filtered.add(r"\d(", &opts), Err(missing_paren));
+}
@@ -0,0 +1,92 @@
+use re2_rs_wrapper::ast::{self, Ast, ClassRange, ParseFlags};
+use re2_rs_wrapper::Error;
+
+fn lit(s: &str) -> Ast {
+    Ast::LiteralString { s: s.to_string(), fold_case: false }
+}
+
+fn digits() -> Ast {
+    Ast::CharClass(vec![ClassRange { lo: '0' as u32, hi: '9' as u32 }])
+}
+
+#[test]
+fn ast_literal_string() {
+    assert_eq!(ast::parse("hello", ParseFlags::LIKE_PERL).unwrap(), lit("hello"));
+    assert_eq!(
+        ast::parse("x", ParseFlags::LIKE_PERL).unwrap(),
+        Ast::Literal { c: 'x', fold_case: false }
+    );
+}
+
+#[test]
+fn ast_named_capture_with_repeat() {
+    let tree = ast::parse(r"(?P<year>\d{4})-(\d{2,})", ParseFlags::default()).unwrap();
+    assert_eq!(
+        tree,
+        Ast::Concat(vec![
+            Ast::Capture {
+                sub: Box::new(Ast::Repeat { sub: Box::new(digits()), min: 4, max: Some(4), greedy: true }),
+                index: 1,
+                name: Some("year".to_string()),
+            },
+            Ast::Literal { c: '-', fold_case: false },
+            Ast::Capture {
+                sub: Box::new(Ast::Repeat { sub: Box::new(digits()), min: 2, max: None, greedy: true }),
+                index: 2,
+                name: None,
+            },
+        ])
+    );
+}
+
+#[test]
+fn ast_anchors_and_greediness() {
+    let tree = ast::parse(r"^a*?$", ParseFlags::LIKE_PERL).unwrap();
+    assert_eq!(
+        tree,
+        Ast::Concat(vec![
+            Ast::BeginText,
+            Ast::Star { sub: Box::new(Ast::Literal { c: 'a', fold_case: false }), greedy: false },
+            Ast::EndText { was_dollar: true },
+        ])
+    );
+
+    // Without ONE_LINE, ^ and $ are line anchors
+    let tree = ast::parse(r"^\b$", ParseFlags::PERL_B).unwrap();
+    assert_eq!(tree, Ast::Concat(vec![Ast::BeginLine, Ast::WordBoundary, Ast::EndLine]));
+}
+
+#[test]
+fn ast_alternation_and_classes() {
+    let tree = ast::parse(r"foo|ba[rz]", ParseFlags::LIKE_PERL).unwrap();
+    assert!(matches!(tree, Ast::Alternate(ref subs) if subs.len() == 2));
+
+    // An empty class is kept as-is; RE2 only rewrites it to NoMatch when simplifying
+    let tree = ast::parse(r"[^\x00-\x{10FFFF}]", ParseFlags::LIKE_PERL).unwrap();
+    assert_eq!(tree, Ast::CharClass(vec![]));
+
+    let tree = ast::parse(r"(?s).", ParseFlags::LIKE_PERL).unwrap();
+    assert_eq!(tree, Ast::AnyChar);
+}
+
+#[test]
+fn ast_fold_case_literal() {
+    let tree = ast::parse("abc", ParseFlags::LIKE_PERL | ParseFlags::FOLD_CASE).unwrap();
+    assert_eq!(tree, Ast::LiteralString { s: "abc".to_string(), fold_case: true });
+}
+
+#[test]
+fn ast_flags_control_syntax() {
+    // Perl classes need PERL_CLASSES
+    assert!(matches!(ast::parse(r"\d", ParseFlags::NONE), Err(Error::Compile(_))));
+    assert_eq!(ast::parse(r"\d", ParseFlags::PERL_CLASSES).unwrap(), digits());
+
+    // LITERAL treats the pattern as plain text
+    assert_eq!(ast::parse(r"a.b", ParseFlags::LITERAL).unwrap(), lit("a.b"));
+}
+
+#[test]
+fn ast_parse_error_message() {
+    let err = ast::parse(r"a{2,1}", ParseFlags::LIKE_PERL).unwrap_err();
+    assert_eq!(err, Error::Compile("invalid repetition size: {2,1}".to_string()));
+}
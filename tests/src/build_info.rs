@@ -0,0 +1,29 @@
+use re2_rs_wrapper::{build_info, has_icu};
+
+#[test]
+fn build_info_reports_vendored_versions() {
+    let info = build_info();
+    assert_eq!(info.re2_version, "2025-08-12");
+    assert_eq!(info.abseil_version, "20250512.1");
+    assert!(!info.cxx_compiler.is_empty());
+    assert!(info.cxx_flags.contains("c++17"), "{}", info.cxx_flags);
+}
+
+#[test]
+fn build_info_icu_versions_follow_the_engine() {
+    let info = build_info();
+    assert_eq!(info.icu_version.is_some(), has_icu());
+    assert_eq!(info.unicode_version.is_some(), has_icu());
+    if let Some(unicode) = &info.unicode_version {
+        let major: u32 = unicode.split('.').next().unwrap().parse().unwrap();
+        assert!(major >= 10, "{}", unicode);
+    }
+}
+
+#[test]
+fn build_info_display_is_one_line() {
+    let text = build_info().to_string();
+    assert!(text.starts_with("RE2 2025-08-12, Abseil 20250512.1, "), "{}", text);
+    assert_eq!(text.contains("ICU "), has_icu());
+    assert!(!text.contains('\n'));
+}
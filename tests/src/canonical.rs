@@ -0,0 +1,87 @@
+use re2_rs_wrapper::ast::{self, ParseFlags};
+use re2_rs_wrapper::Regex;
+
+const PROBES: &[&str] = &[
+    "", "a", "aa", "aaa", "aaaa", "ab", "abc", "cba", "b", "x1", "12", "foo", "FOO", "foobar",
+    "a\nb", "hello world", "2024-12-31", "x\na", "a\nx", "x\na\nx", "a\n",
+];
+
+// Both patterns agree on every probe, anchored and unanchored
+fn assert_equivalent(a: &str, b: &str) {
+    let ra = Regex::new(a).unwrap();
+    let rb = Regex::new(b).unwrap_or_else(|e| panic!("{:?} does not compile: {}", b, e));
+    for p in PROBES {
+        assert_eq!(ra.full_match(p), rb.full_match(p), "full_match({:?}): {:?} vs {:?}", p, a, b);
+        assert_eq!(ra.partial_match(p), rb.partial_match(p), "partial_match({:?}): {:?} vs {:?}", p, a, b);
+    }
+}
+
+#[test]
+fn canonicalize_dedupes_equivalent_spellings() {
+    let a = ast::canonicalize("[cba]", ParseFlags::LIKE_PERL).unwrap();
+    let b = ast::canonicalize("[a-c]", ParseFlags::LIKE_PERL).unwrap();
+    let c = ast::canonicalize("a|b|c", ParseFlags::LIKE_PERL).unwrap();
+    assert_eq!(a, "[a-c]");
+    assert_eq!(a, b);
+    assert_eq!(a, c);
+}
+
+#[test]
+fn simplify_expands_counted_repeats() {
+    assert_eq!(ast::canonicalize("a{2,3}", ParseFlags::LIKE_PERL).unwrap(), "a{2,3}");
+    assert_eq!(ast::simplify("a{2,3}", ParseFlags::LIKE_PERL).unwrap(), "aaa?");
+    assert_eq!(
+        ast::simplify("a{2,3}", ParseFlags::LIKE_PERL).unwrap(),
+        ast::simplify("aaa?", ParseFlags::LIKE_PERL).unwrap()
+    );
+}
+
+#[test]
+fn canonical_and_simplified_forms_compile_back() {
+    let patterns = [
+        r"a{2,3}",
+        r"(?i)foo",
+        r"[cba]+",
+        r"^\d{4}-\d{2}-\d{2}$",
+        r"(?s)a.b",
+        r"x*?1|12",
+        r"(?P<name>foo)(bar)?",
+        r"\bhello\s+world\b",
+        r"(?:a|ab)(?:c|bcd)?",
+        r"(?m)^a$",
+        r"(?m)^foo|bar$",
+        r"(?ms)^a.b$",
+        r"^a$",
+        r"(?m:^a)$",
+    ];
+    for p in patterns {
+        let canonical = ast::canonicalize(p, ParseFlags::LIKE_PERL).unwrap();
+        let simplified = ast::simplify(p, ParseFlags::LIKE_PERL).unwrap();
+        assert_equivalent(p, &canonical);
+        assert_equivalent(p, &simplified);
+    }
+}
+
+#[test]
+fn canonical_form_keeps_dot_newline_semantics() {
+    // RE2 prints AnyChar as `.`, which only means "any char" under (?s)
+    assert_eq!(ast::canonicalize(r"(?s)a.b", ParseFlags::LIKE_PERL).unwrap(), "(?s:a.b)");
+    assert_eq!(ast::canonicalize(r"a.b", ParseFlags::LIKE_PERL).unwrap(), r"a[^\n]b");
+}
+
+#[test]
+fn canonical_form_keeps_multi_line_anchors() {
+    // RE2 prints the multi-line anchors as bare ^ and $, which only mean that under (?m)
+    assert_eq!(ast::canonicalize(r"(?m)^a$", ParseFlags::LIKE_PERL).unwrap(), "(?m:^a$)");
+    assert_eq!(ast::canonicalize(r"(?ms)^.$", ParseFlags::LIKE_PERL).unwrap(), "(?ms:^.$)");
+    assert_eq!(ast::simplify(r"(?m)^a{2}$", ParseFlags::LIKE_PERL).unwrap(), "(?m:^aa$)");
+
+    let re = Regex::new(&ast::canonicalize(r"(?m)^a$", ParseFlags::LIKE_PERL).unwrap()).unwrap();
+    assert!(re.partial_match("x\na"));
+}
+
+#[test]
+fn canonicalize_reports_parse_errors() {
+    assert!(ast::canonicalize("(", ParseFlags::LIKE_PERL).is_err());
+    assert!(ast::simplify("a**", ParseFlags::LIKE_PERL).is_err());
+}
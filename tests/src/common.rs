@@ -1,4 +1,4 @@
-use re2_rs_wrapper::{Options, Regex};
+use re2_rs_wrapper::{has_icu, Engine, Error, Options, Regex};
 
 #[test]
 fn wrapper_partial_match() {
@@ -70,6 +70,15 @@ fn wrapper_lookahead_is_not_supported() {
     assert!(Regex::new(r"(?<=foo)bar").is_err());   // lookbehind
 }
 
+#[test]
+fn wrapper_invalid_pattern_is_compile_error() {
+    match Regex::new("a(") {
+        Err(Error::Compile(msg)) => assert!(msg.contains("missing )"), "{}", msg),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("pattern should not compile"),
+    }
+}
+
 //Unicode
 #[test]
 fn unicode_greek_letters_match() {
@@ -169,3 +178,99 @@ fn full_captures_returns_none_if_no_match() {
     let re = Regex::new(r"(\w+)=(\d+)").unwrap();
     assert!(re.full_captures("not a pair").is_none());
 }
+
+#[test]
+fn try_variants_agree_with_infallible_ones() {
+    let re = Regex::new(r"(\w+)=(\d+)").unwrap();
+    assert_eq!(re.try_full_match("a=1"), Ok(true));
+    assert_eq!(re.try_full_match("a=x"), Ok(false));
+    assert_eq!(re.try_partial_match("x a=1 y"), Ok(true));
+    assert_eq!(re.try_partial_match("none"), Ok(false));
+    assert_eq!(re.try_partial_captures("x a=1 y"), Ok(re.partial_captures("x a=1 y")));
+    assert_eq!(re.try_full_captures("a=1"), Ok(Some(vec![Some("a=1"), Some("a"), Some("1")])));
+    assert_eq!(re.try_full_captures("x a=1"), Ok(None));
+    assert_eq!(re.try_replace_one("a=1 b=2", r"\2=\1"), Ok(Some("1=a b=2".to_string())));
+    assert_eq!(re.try_replace_all("a=1 b=2", r"\2=\1"), Ok(Some("1=a 2=b".to_string())));
+    assert_eq!(re.try_replace_all("none", "x"), Ok(None));
+}
+
+#[test]
+#[cfg_attr(feature = "system-re2", ignore = "needs the vendored RE2")]
+fn required_prefix_literal() {
+    let re = Regex::new(r"hello\s+\w+").unwrap();
+    assert_eq!(re.required_prefix(), Some(("hello".to_string(), false)));
+
+    // Anchored patterns and capture groups are seen through
+    let re = Regex::new(r"^GET /api/(\d+)").unwrap();
+    assert_eq!(re.required_prefix(), Some(("GET /api/".to_string(), false)));
+    let re = Regex::new(r"(foo)bar").unwrap();
+    assert_eq!(re.required_prefix(), Some(("foo".to_string(), false)));
+}
+
+#[test]
+#[cfg_attr(feature = "system-re2", ignore = "needs the vendored RE2")]
+fn required_prefix_case_folded() {
+    let re = Regex::new(r"(?i)abc\d").unwrap();
+    let (prefix, foldcase) = re.required_prefix().unwrap();
+    assert_eq!(prefix.to_lowercase(), "abc");
+    assert!(foldcase);
+}
+
+#[test]
+fn required_prefix_none() {
+    assert_eq!(Regex::new(r"\d+abc").unwrap().required_prefix(), None);
+    assert_eq!(Regex::new(r"foo|bar").unwrap().required_prefix(), None);
+    assert_eq!(Regex::new(r"a*b").unwrap().required_prefix(), None);
+}
+
+#[test]
+fn wrapper_replace_result_larger_than_one_mib() {
+    let re = Regex::new("a").unwrap();
+    let text = "a".repeat((1 << 20) + 3);
+    let out = re.replace_all(&text, "bb").unwrap();
+    assert_eq!(out.len(), 2 * text.len());
+    assert!(out.bytes().all(|b| b == b'b'));
+}
+
+// Offsets past i32::MAX used to overflow the int endpos passed to RE2::Match.
+// Allocates (zero-filled, mostly untouched) 2.2 GiB, so not run by default.
+#[ignore]
+#[test]
+fn wrapper_match_beyond_2gib() {
+    let at = (i32::MAX as usize) + 100;
+    let mut bytes = vec![0u8; at + 16];
+    bytes[at..at + 6].copy_from_slice(b"needle");
+    let text = String::from_utf8(bytes).unwrap();
+
+    let re = Regex::new("(needle)").unwrap();
+    assert!(re.partial_match(&text));
+    let caps = re.partial_captures(&text).unwrap();
+    let m = caps[1].unwrap();
+    assert_eq!(m, "needle");
+    assert_eq!(m.as_ptr() as usize - text.as_ptr() as usize, at);
+    let too_large = Error::TextTooLarge { len: text.len(), max: Regex::MAX_REPLACE_ALL_LEN };
+    assert_eq!(re.try_replace_all(&text, "x"), Err(too_large));
+    assert_eq!(re.replace_all(&text, "x"), None);
+}
+
+#[test]
+fn replace_all_rejects_texts_it_cannot_count() {
+    let max = Regex::MAX_REPLACE_ALL_LEN;
+    assert_eq!(max, i32::MAX as usize - 1);
+    assert_eq!(Regex::check_replace_all_len(0), Ok(()));
+    assert_eq!(Regex::check_replace_all_len(max), Ok(()));
+    assert_eq!(Regex::check_replace_all_len(max + 1), Err(Error::TextTooLarge { len: max + 1, max }));
+    assert_eq!(
+        Regex::check_replace_all_len(usize::MAX).unwrap_err().to_string(),
+        format!("text of {} bytes exceeds the limit of {} bytes", usize::MAX, max)
+    );
+}
+
+#[test]
+fn wrapper_icu_engine_needs_the_icu_build() {
+    let opts = Options::new().engine(Engine::Icu).log_errors(false);
+    let result = Regex::with_options(r"\p{Greek}+", &opts);
+    assert_eq!(result.is_ok(), has_icu(), "{:?}", result.as_ref().err());
+    // The default engine never changes with the build
+    assert!(Regex::new(r"\p{Alphabetic}").is_err());
+}
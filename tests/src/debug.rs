@@ -0,0 +1,57 @@
+use re2_rs_wrapper::{debug, Regex};
+
+#[test]
+fn dump_lists_instructions() {
+    let re = Regex::new("abc").unwrap();
+    let dump = debug::dump(&re);
+    assert!(dump.contains("byte [61-61]"), "{}", dump);
+    assert!(dump.contains("byte [63-63]"), "{}", dump);
+    assert!(dump.contains("match! 0"), "{}", dump);
+}
+
+#[test]
+fn dump_marks_case_folding() {
+    let re = Regex::new("(?i)k").unwrap();
+    assert!(debug::dump(&re).contains("byte/i"));
+}
+
+#[test]
+fn dump_unanchored_adds_leading_loop() {
+    let re = Regex::new("abc").unwrap();
+    let anchored = debug::dump(&re);
+    let unanchored = debug::dump_unanchored(&re);
+    assert!(unanchored.lines().count() > anchored.lines().count(), "{}", unanchored);
+    assert!(unanchored.contains("byte [00-ff]"), "{}", unanchored);
+}
+
+#[test]
+fn dump_byte_map_covers_all_bytes() {
+    let re = Regex::new("[a-c]x").unwrap();
+    let map = debug::dump_byte_map(&re);
+    let lines: Vec<&str> = map.lines().collect();
+    assert!(lines.first().unwrap().starts_with("[00-"), "{}", map);
+    assert!(lines.last().unwrap().contains("-ff]"), "{}", map);
+    assert!(map.contains("[61-63] -> "), "{}", map);
+    assert!(map.contains("[78-78] -> "), "{}", map);
+}
+
+#[test]
+fn to_dot_is_a_digraph() {
+    let re = Regex::new("a+|b").unwrap();
+    let dot = debug::to_dot(&re);
+    assert!(dot.starts_with("digraph prog {"), "{}", dot);
+    assert!(dot.trim_end().ends_with('}'), "{}", dot);
+    assert!(dot.contains("start -> "), "{}", dot);
+    assert!(dot.contains("unanchored -> "), "{}", dot);
+    assert!(dot.contains("[style=dashed]"), "{}", dot);
+    assert!(dot.contains("match! 0"), "{}", dot);
+}
+
+#[test]
+fn dump_of_prefix_anchored_pattern() {
+    // RE2 strips a ^literal prefix before compiling; the program matches the rest
+    let re = Regex::new("^abc[0-9]").unwrap();
+    let dump = debug::dump(&re);
+    assert!(dump.contains("byte [30-39]"), "{}", dump);
+    assert!(!dump.contains("byte [61-61]"), "{}", dump);
+}
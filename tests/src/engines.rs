@@ -0,0 +1,87 @@
+use re2_rs_wrapper::{has_icu, Anchor, Engine, Error, FilteredRegexSet, Options, Regex, RegexSet};
+
+fn icu() -> Options {
+    Options::new().engine(Engine::Icu)
+}
+
+#[test]
+fn engines_coexist() {
+    assert!(has_icu());
+    // The default engine does not know ICU-only properties, whatever else is linked in
+    assert!(matches!(Regex::new(r"\p{Emoji}"), Err(Error::Compile(_))));
+    let re = Regex::with_options(r"^\p{Emoji}+$", &icu()).unwrap();
+    assert!(re.full_match("😀👍"));
+    assert!(!re.full_match("abc"));
+}
+
+#[test]
+fn engines_agree_without_icu_properties() {
+    let cases: &[(&str, &str)] = &[
+        (r"(?P<user>\w+)@(\w+)\.com", "mail bob@example.com now"),
+        (r"(?m)^(\d+)$", "x\n42\ny"),
+        (r"(?-m:^)ab|c$", "zc\nab"),
+        (r"(?s)a.b", "a\nb"),
+        (r"a.b", "a\nb axb"),
+        (r"(a+?)(a*)", "aaaa"),
+        (r"(?i)straße|κόσμος", "ΚΌΣΜΟΣ"),
+        (r"x(?:ab){2,3}?\b", "xababab!"),
+        (r"[^a-c\p{Greek}]+", "abδxyz"),
+    ];
+    for &(pattern, text) in cases {
+        let plain = Regex::new(pattern).unwrap();
+        let icu = Regex::with_options(pattern, &icu()).unwrap();
+        assert_eq!(plain.num_captures(), icu.num_captures(), "{:?}", pattern);
+        assert_eq!(plain.partial_captures(text), icu.partial_captures(text), "{:?} on {:?}", pattern, text);
+    }
+}
+
+#[test]
+fn engines_keep_options() {
+    // Case folding applies where the pattern allows it, and only there
+    let opts = icu().case_insensitive(true);
+    let re = Regex::with_options(r"\p{Emoji}x(?-i:Y)", &opts).unwrap();
+    assert!(re.partial_match("😀XY"));
+    assert!(!re.partial_match("😀Xy"));
+
+    // POSIX syntax still rejects Perl extensions, and \p with them
+    let opts = icu().posix_syntax(true).log_errors(false);
+    assert!(Regex::with_options(r"\d", &opts).is_err());
+    assert!(Regex::with_options(r"a|b*", &opts).unwrap().full_match("bb"));
+}
+
+#[test]
+fn engines_report_icu_parse_errors() {
+    let opts = icu().log_errors(false);
+    let err = Regex::with_options(r"\p{NoSuchProperty}", &opts).err().unwrap();
+    assert_eq!(err, Error::Compile(r"invalid character class range: \p{NoSuchProperty}".into()));
+}
+
+#[test]
+fn engines_in_sets() {
+    let mut set = RegexSet::new([r"\p{Emoji_Presentation}", r"\d+"], &icu(), Anchor::Unanchored).unwrap();
+    set.compile().unwrap();
+    assert_eq!(set.matches("go 🚀").unwrap(), vec![0]);
+    assert_eq!(set.matches("go 42").unwrap(), vec![1]);
+
+    let mut filtered = FilteredRegexSet::new(1).unwrap();
+    filtered.add(r"rocket \p{Emoji_Presentation}", &icu()).unwrap();
+    assert!(filtered.add(r"rocket \p{Emoji_Presentation}", &Options::new().log_errors(false)).is_err());
+    filtered.compile().unwrap();
+    assert_eq!(filtered.matches("rocket 🚀").unwrap(), vec![0]);
+}
+
+#[test]
+fn engines_icu_crate_defaults_to_icu() {
+    let re = re2_rs_icu::Regex::new(r"^\p{Emoji}+$").unwrap();
+    assert!(re.full_match("😀👍"));
+
+    // Its options can be used anywhere the wrapper's are, and still pick the engine
+    let opts = re2_rs_icu::Options::new().case_insensitive(true);
+    let mut set = RegexSet::new([r"\p{Emoji_Presentation}", "abc"], &opts, Anchor::Unanchored).unwrap();
+    set.compile().unwrap();
+    assert_eq!(set.matches("ABC 🚀").unwrap(), vec![0, 1]);
+
+    let plain = re2_rs_icu::Options::new().engine(Engine::Plain).log_errors(false);
+    assert!(re2_rs_icu::Regex::with_options(r"\p{Emoji}", &plain).is_err());
+    assert!(Regex::new(r"\p{Emoji}").is_err());
+}
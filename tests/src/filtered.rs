@@ -0,0 +1,142 @@
+use re2_rs_wrapper::{Error, FilteredRegexSet, Options};
+
+// Naive atom matcher: indices of atoms occurring in the lowercased text.
+fn found_atoms(atoms: &[String], text: &str) -> Vec<usize> {
+    let lower = text.to_lowercase();
+    (0..atoms.len()).filter(|&i| lower.contains(atoms[i].as_str())).collect()
+}
+
+fn build(patterns: &[&str]) -> (FilteredRegexSet, Vec<String>) {
+    let mut f = FilteredRegexSet::new(3).unwrap();
+    let opts = Options::new();
+    for (i, p) in patterns.iter().enumerate() {
+        assert_eq!(f.add(p, &opts).unwrap(), i);
+    }
+    let atoms = f.compile().unwrap();
+    (f, atoms)
+}
+
+#[test]
+fn filtered_compile_returns_lowercased_atoms() {
+    let (_, atoms) = build(&[r"Hello\s+World", r"abc\d+"]);
+    assert!(atoms.contains(&"hello".to_string()));
+    assert!(atoms.contains(&"world".to_string()));
+    assert!(atoms.contains(&"abc".to_string()));
+}
+
+#[test]
+fn filtered_all_matches_with_found_atoms() {
+    let (f, atoms) = build(&[r"hello\s+world", r"abc\d+", r"xyz[a-z]+"]);
+
+    let text = "say hello   world and abc123";
+    let found = found_atoms(&atoms, text);
+    assert_eq!(f.all_matches(text, &found).unwrap(), vec![0, 1]);
+    assert_eq!(f.first_match(text, &found).unwrap(), Some(0));
+
+    // Atoms present but the full regex does not match
+    let text = "world, hello, abc";
+    let found = found_atoms(&atoms, text);
+    assert_eq!(f.all_potentials(&found).unwrap(), vec![0, 1]);
+    assert!(f.all_matches(text, &found).unwrap().is_empty());
+    assert_eq!(f.first_match(text, &found).unwrap(), None);
+}
+
+#[test]
+fn filtered_unfiltered_patterns_always_potential() {
+    // `\d+` has no atoms, so it can never be filtered out
+    let (f, _) = build(&[r"\d+", r"needle"]);
+    assert_eq!(f.all_potentials(&[]).unwrap(), vec![0]);
+    assert_eq!(f.all_matches("42", &[]).unwrap(), vec![0]);
+}
+
+#[test]
+fn filtered_slow_first_match_before_compile() {
+    let mut f = FilteredRegexSet::new(3).unwrap();
+    f.add("foo", &Options::new()).unwrap();
+    f.add("bar", &Options::new()).unwrap();
+    assert_eq!(f.slow_first_match("a bar"), Ok(Some(1)));
+    assert_eq!(f.slow_first_match("none"), Ok(None));
+}
+
+#[test]
+fn filtered_lifecycle_errors() {
+    let mut f = FilteredRegexSet::new(3).unwrap();
+    assert!(matches!(f.add(r"(?<=x)y", &Options::new()), Err(Error::Compile(_))));
+    f.add("foo", &Options::new()).unwrap();
+    assert_eq!(f.all_matches("foo", &[]), Err(Error::NotCompiled));
+
+    f.compile().unwrap();
+    assert_eq!(f.add("bar", &Options::new()), Err(Error::AlreadyCompiled));
+    assert_eq!(f.compile(), Err(Error::AlreadyCompiled));
+}
+
+#[test]
+fn filtered_rejects_unknown_atom_index() {
+    let (f, atoms) = build(&["foobar"]);
+    let err = Error::AtomOutOfRange { index: atoms.len(), len: atoms.len() };
+    assert_eq!(f.all_matches("foobar", &[atoms.len()]), Err(err.clone()));
+    assert_eq!(f.first_match("foobar", &[0, atoms.len()]), Err(err.clone()));
+    assert_eq!(f.all_potentials(&[atoms.len()]), Err(err));
+    assert_eq!(f.all_potentials(&[usize::MAX]).unwrap_err().to_string(),
+               format!("atom index {} out of range ({} atoms)", usize::MAX, atoms.len()));
+}
+
+#[test]
+fn filtered_find_atoms_overlapping() {
+    let mut f = FilteredRegexSet::new(1).unwrap();
+    for p in ["he", "she", "his", "hers"] {
+        f.add(p, &Options::new()).unwrap();
+    }
+    let atoms = f.compile().unwrap();
+    let idx = |a: &str| atoms.iter().position(|x| x == a).unwrap();
+
+    let found = f.find_atoms("USHERS").unwrap();
+    let mut expected = vec![idx("he"), idx("she"), idx("hers")];
+    expected.sort();
+    assert_eq!(found, expected);
+    assert!(f.find_atoms("xyz").unwrap().is_empty());
+}
+
+#[test]
+fn filtered_matches_end_to_end() {
+    let mut f = FilteredRegexSet::new(3).unwrap();
+    let opts = Options::new();
+    f.add(r"error:\s+\d{3}", &opts).unwrap();
+    f.add(r"(?i)WARNING", &opts).unwrap();
+    f.add(r"user=\w+", &opts).unwrap();
+    f.compile().unwrap();
+
+    assert_eq!(f.matches("ERROR: 500 for user=bob").unwrap(), vec![2]);
+    assert_eq!(f.matches("error:  404, warning").unwrap(), vec![0, 1]);
+    assert!(f.is_match("a Warning").unwrap());
+    assert!(!f.is_match("all good").unwrap());
+    assert_eq!(f.matches("x"), Ok(vec![]));
+}
+
+#[test]
+fn filtered_matches_many_patterns() {
+    let mut f = FilteredRegexSet::new(3).unwrap();
+    let opts = Options::new();
+    for i in 0..5000 {
+        f.add(&format!(r"rule{}x\d+", i), &opts).unwrap();
+    }
+    f.compile().unwrap();
+
+    assert_eq!(f.matches("hit rule42x7 and rule4999x1 but not rule7x").unwrap(), vec![42, 4999]);
+}
+
+#[test]
+fn filtered_matches_requires_compile() {
+    let f = FilteredRegexSet::new(3).unwrap();
+    assert_eq!(f.matches("abc"), Err(Error::NotCompiled));
+}
+
+#[test]
+fn filtered_matches_lowercase_text_per_rune() {
+    // str::to_lowercase would turn the final 'Σ' into 'ς', and RE2's atom has 'σ'
+    let (f, atoms) = build(&["ΟΔΟΣ", "(?i)οδοσ"]);
+    assert!(atoms.iter().all(|a| a.ends_with('σ')), "{:?}", atoms);
+    assert_eq!(f.slow_first_match("ΟΔΟΣ").unwrap(), Some(0));
+    assert_eq!(f.matches("ΟΔΟΣ").unwrap(), vec![0, 1]);
+    assert_eq!(f.matches("ο ΔΡΟΜΟΣ ΟΔΟΣ").unwrap(), vec![0, 1]);
+}
@@ -0,0 +1,57 @@
+use re2_rs_wrapper::{hooks, Options, Regex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Text on which the DFA for `(a|b)*a(a|b){n}` needs ~2^n states
+fn random_ab(len: usize) -> String {
+    let mut x: u32 = 0x2545_f491;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            if x & 1 == 0 { 'a' } else { 'b' }
+        })
+        .collect()
+}
+
+// One test for both hooks: they are process-wide, so parallel tests would race
+#[test]
+fn dfa_hooks_report_thrashing_pattern() {
+    let pattern = "(?:a|b)*a(?:a|b){16}c";
+    let resets = Arc::new(Mutex::new(Vec::new()));
+    let failures = Arc::new(AtomicUsize::new(0));
+
+    let seen = Arc::clone(&resets);
+    hooks::on_dfa_cache_reset(move |info| seen.lock().unwrap().push(info.clone()));
+    let count = Arc::clone(&failures);
+    hooks::on_dfa_search_failure(move |_| {
+        count.fetch_add(1, Ordering::Relaxed);
+    });
+
+    let re = Regex::new(pattern).unwrap();
+    let text = random_ab(1 << 17);
+    assert!(!re.partial_match(&text));
+    // Reported as written, not as rewritten to Unicode tables
+    let unicode_pattern = r"(?:a|\d)*a(?:a|b|\d){16}c";
+    let unicode = Regex::with_options(unicode_pattern, &Options::new().unicode_perl_classes(true)).unwrap();
+    assert!(!unicode.partial_match(&text));
+
+    hooks::clear_dfa_cache_reset();
+    hooks::clear_dfa_search_failure();
+
+    let seen = resets.lock().unwrap().clone();
+    assert!(!seen.is_empty(), "expected the DFA to reset its cache");
+    let ours: Vec<_> = seen.iter().filter(|r| r.pattern.as_deref() == Some(pattern)).collect();
+    assert!(!ours.is_empty(), "{:?}", seen.first());
+    assert!(ours[0].state_budget > 0);
+    assert!(ours[0].state_cache_size > 0);
+    assert!(seen.iter().any(|r| r.pattern.as_deref() == Some(unicode_pattern)), "{:?}", seen.last());
+    let failed = failures.load(Ordering::Relaxed);
+    assert!(failed > 0, "expected the DFA to give up");
+
+    // Cleared hooks no longer fire
+    assert!(!re.partial_match(&text));
+    assert_eq!(resets.lock().unwrap().len(), seen.len());
+    assert_eq!(failures.load(Ordering::Relaxed), failed);
+}
@@ -0,0 +1,29 @@
+use re2_rs_wrapper::logging::{self, Severity};
+use re2_rs_wrapper::{Options, Regex};
+use std::sync::{Arc, Mutex};
+
+// One test: the logger is process-wide, so parallel tests would race
+#[test]
+fn logger_receives_errors_unless_silenced() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&records);
+    logging::set_logger(move |r| {
+        sink.lock().unwrap().push((r.severity, r.file.to_string(), r.line, r.message.to_string()));
+    });
+
+    assert!(Regex::new("logged-pattern(").is_err());
+    let quiet = Options::new().log_errors(false);
+    assert!(Regex::with_options("silenced-pattern(", &quiet).is_err());
+
+    logging::clear_logger();
+
+    let records = records.lock().unwrap();
+    let logged: Vec<_> = records.iter().filter(|r| r.3.contains("logged-pattern(")).collect();
+    assert_eq!(logged.len(), 1, "{:?}", *records);
+    let (severity, file, line, message) = logged[0];
+    assert_eq!(*severity, Severity::Error);
+    assert!(file.ends_with("re2.cc"), "{}", file);
+    assert!(*line > 0);
+    assert!(message.contains("missing )"), "{}", message);
+    assert!(!records.iter().any(|r| r.3.contains("silenced-pattern(")), "{:?}", *records);
+}
@@ -0,0 +1,67 @@
+use re2_rs_wrapper::{memory, Options, Regex};
+
+#[test]
+fn memory_heap_bytes_cover_the_program() {
+    let small = Regex::new(r"abc").unwrap();
+    let large = Regex::new(r"[a-q][^u-z]{13}x").unwrap();
+    assert!(small.approx_heap_bytes() > 0);
+    assert!(large.approx_heap_bytes() > small.approx_heap_bytes());
+    // Measuring builds nothing
+    assert_eq!(large.approx_heap_bytes(), large.approx_heap_bytes());
+}
+
+#[test]
+#[cfg_attr(feature = "system-re2", ignore = "needs the vendored RE2")]
+fn memory_heap_bytes_grow_with_dfa_use() {
+    let re = Regex::new(r"[a-q][^u-z]{13}x").unwrap();
+    let fresh = re.approx_heap_bytes();
+
+    let text = "abcdefghijklmnopqrstuvw".repeat(1 << 10);
+    assert!(!re.partial_match(&text));
+    let searched = re.approx_heap_bytes();
+    assert!(searched > fresh, "{} -> {}", fresh, searched);
+
+    // Locating the start of a match also compiles the reverse program and builds its DFA
+    assert!(re.partial_captures(&format!("{}abcdefghijklmnx", text)).is_some());
+    assert!(re.approx_heap_bytes() > searched);
+}
+
+#[test]
+fn memory_max_heap_bytes_is_the_max_mem_budget() {
+    let max_mem = 1 << 20;
+    let re = Regex::with_options(r"(?:a|b)*a(?:a|b){12}c", &Options::new().max_mem(max_mem)).unwrap();
+    let max = re.max_heap_bytes();
+    assert!(max >= max_mem as usize && max < max_mem as usize + 1024, "{}", max);
+    assert!(re.approx_heap_bytes() < max);
+
+    let default = Regex::new("a").unwrap();
+    assert!(default.max_heap_bytes() >= 8 << 20);
+}
+
+#[test]
+#[cfg_attr(feature = "system-re2", ignore = "needs the vendored RE2")]
+fn memory_heap_bytes_stay_within_max_mem() {
+    let max_mem = 1 << 20;
+    let re = Regex::with_options(r"(?:a|b)*a(?:a|b){12}c", &Options::new().max_mem(max_mem)).unwrap();
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let text: String = (0..1 << 16)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            if state & 1 == 0 { 'a' } else { 'b' }
+        })
+        .collect();
+    assert!(!re.partial_match(&text));
+    let bytes = re.approx_heap_bytes();
+    assert!(bytes > (max_mem as usize) / 4 && bytes <= re.max_heap_bytes(), "{}", bytes);
+}
+
+#[test]
+fn memory_counters_track_live_regexes() {
+    // Other tests create regexes concurrently, so only lower bounds hold
+    let regexes: Vec<Regex> = (0..50).map(|i| Regex::new(&format!("a{{{}}}b", i)).unwrap()).collect();
+    let counted: usize = regexes.iter().map(Regex::approx_heap_bytes).sum();
+    assert!(memory::live_regexes() >= regexes.len());
+    assert!(memory::total_bytes() >= counted);
+}
@@ -1,4 +1,4 @@
-use re2_rs_wrapper::{Regex, Options};
+use re2_rs_icu::{Regex, Options};
 
 #[test]
 fn options_posix_longest() {
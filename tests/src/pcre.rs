@@ -0,0 +1,53 @@
+use re2_rs_wrapper::ast::Ast;
+use re2_rs_wrapper::{pcre_compatible, pcre_incompatibility, Error, PcreIncompatibility};
+
+#[test]
+fn pcre_compatible_plain_patterns() {
+    for p in ["abc", "a+b*c?", r"\Aabc\z", "^abc", "(?m)abc$", "[a-z]{2,}", r"\d+\.\d+"] {
+        assert!(pcre_compatible(p), "{:?} should mimic PCRE", p);
+        assert_eq!(pcre_incompatibility(p).unwrap(), None);
+    }
+}
+
+#[test]
+fn pcre_incompatible_empty_repetition() {
+    for p in ["(a*)*", "(a|)+", "(?:a?){2,}", "x(\\b)*"] {
+        assert!(!pcre_compatible(p), "{:?} should not mimic PCRE", p);
+        match pcre_incompatibility(p).unwrap() {
+            Some(PcreIncompatibility::EmptyRepetition(_)) => {}
+            other => panic!("{:?}: unexpected {:?}", p, other),
+        }
+    }
+    // A bounded repeat of an empty-capable operand is fine
+    assert!(pcre_compatible("(a*){2,3}"));
+}
+
+#[test]
+fn pcre_incompatibility_names_the_repeat() {
+    let Some(PcreIncompatibility::EmptyRepetition(node)) = pcre_incompatibility("x(a*)+y").unwrap() else {
+        panic!("expected an empty repetition");
+    };
+    assert!(matches!(node, Ast::Plus { .. }), "{:?}", node);
+}
+
+#[test]
+fn pcre_incompatible_anchors_and_vertical_tab() {
+    assert_eq!(pcre_incompatibility("abc$").unwrap(), Some(PcreIncompatibility::DollarEndOfText));
+    assert_eq!(pcre_incompatibility("(?m)^abc").unwrap(), Some(PcreIncompatibility::MultiLineCaret));
+    assert_eq!(pcre_incompatibility(r"\v").unwrap(), Some(PcreIncompatibility::VerticalTab));
+    assert!(!pcre_compatible("abc$"));
+    assert!(!pcre_compatible("(?m)^abc"));
+    assert!(!pcre_compatible(r"\v"));
+}
+
+#[test]
+fn pcre_incompatibility_display() {
+    let reason = pcre_incompatibility("abc$").unwrap().unwrap();
+    assert!(reason.to_string().contains("$"), "{}", reason);
+}
+
+#[test]
+fn pcre_compatible_invalid_pattern() {
+    assert!(!pcre_compatible("a("));
+    assert!(matches!(pcre_incompatibility("a("), Err(Error::Compile(_))));
+}
@@ -0,0 +1,83 @@
+use re2_rs_wrapper::{CompilePolicy, Error, Options, PolicyLimit, Regex};
+
+#[test]
+fn policy_default_accepts_anything_regex_new_does() {
+    let re = Regex::with_policy(r"(\w+)@(\w+)\.com", &Options::new(), &CompilePolicy::new()).unwrap();
+    assert!(re.partial_match("mail bob@example.com"));
+    assert_eq!(re.num_captures(), 2);
+}
+
+#[test]
+fn policy_pattern_length() {
+    let policy = CompilePolicy::new().max_pattern_len(8);
+    assert!(Regex::with_policy("abcdefgh", &Options::new(), &policy).is_ok());
+    let err = Regex::with_policy("abcdefghi", &Options::new(), &policy).err().unwrap();
+    assert_eq!(err, Error::Policy(PolicyLimit::PatternLength { max: 8, actual: 9 }));
+}
+
+#[test]
+fn policy_capture_count() {
+    let policy = CompilePolicy::new().max_captures(2);
+    assert!(Regex::with_policy("(a)(b)(?:c)", &Options::new(), &policy).is_ok());
+    let err = Regex::with_policy("(a)(b)(c)", &Options::new(), &policy).err().unwrap();
+    assert_eq!(err, Error::Policy(PolicyLimit::CaptureCount { max: 2, actual: 3 }));
+}
+
+#[test]
+#[cfg_attr(feature = "system-re2", ignore = "needs the vendored RE2")]
+fn policy_repeat_count_multiplies_nested_repeats() {
+    let policy = CompilePolicy::new().max_repeat(50);
+    assert!(Regex::with_policy("a{50}b{2,}", &Options::new(), &policy).is_ok());
+    let err = Regex::with_policy("(?:a{5}b){1,20}", &Options::new(), &policy).err().unwrap();
+    assert_eq!(err, Error::Policy(PolicyLimit::RepeatCount { max: 50, actual: 100 }));
+    let err = Regex::with_policy("a{51,}", &Options::new(), &policy).err().unwrap();
+    assert_eq!(err, Error::Policy(PolicyLimit::RepeatCount { max: 50, actual: 51 }));
+}
+
+#[test]
+#[cfg_attr(feature = "system-re2", ignore = "needs the vendored RE2")]
+fn policy_repeat_count_sees_through_zero_bounds() {
+    let policy = CompilePolicy::new().max_repeat(10);
+    let opts = Options::new().log_errors(false);
+    for pattern in ["(?:a{900})*", "(?:a{900}){0,}", "(?:a{900}){0}"] {
+        let err = Regex::with_policy(pattern, &opts, &policy).err().unwrap();
+        assert_eq!(err, Error::Policy(PolicyLimit::RepeatCount { max: 10, actual: 900 }), "{}", pattern);
+    }
+    let err = Regex::with_policy("(?:a{400}){0,2}", &opts, &policy).err().unwrap();
+    assert_eq!(err, Error::Policy(PolicyLimit::RepeatCount { max: 10, actual: 800 }));
+}
+
+#[test]
+fn policy_program_size() {
+    let policy = CompilePolicy::new().max_program_size(100);
+    assert!(Regex::with_policy("abc", &Options::new(), &policy).is_ok());
+    match Regex::with_policy("[a-z]{200}", &Options::new(), &policy).err().unwrap() {
+        Error::Policy(PolicyLimit::ProgramSize { max: 100, actual }) => assert!(actual > 200, "{}", actual),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn policy_max_mem_is_a_ceiling() {
+    let policy = CompilePolicy::new().max_mem(1 << 12);
+    let opts = Options::new().log_errors(false);
+    assert!(Regex::with_policy("abc", &opts, &policy).is_ok());
+    let err = Regex::with_policy("[a-z]{1000}", &opts, &policy).err().unwrap();
+    assert_eq!(err, Error::Policy(PolicyLimit::MaxMem { max: 1 << 12 }));
+    assert_eq!(err.to_string(), "compile policy violated: compiled program exceeds max_mem of 4096 bytes");
+
+    // A lower max_mem in the options is kept, and reported as the limit
+    let opts = Options::new().log_errors(false).max_mem(1 << 10);
+    let err = Regex::with_policy("[a-z]{1000}", &opts, &CompilePolicy::new().max_mem(1 << 20)).err().unwrap();
+    assert_eq!(err, Error::Policy(PolicyLimit::MaxMem { max: 1 << 10 }));
+
+    // Without a policy ceiling, running out of max_mem stays a compile error
+    assert!(matches!(Regex::with_options("[a-z]{1000}", &opts), Err(Error::Compile(_))));
+}
+
+#[test]
+fn policy_leaves_compile_errors_alone() {
+    let policy = CompilePolicy::new().max_repeat(10).max_captures(1);
+    let opts = Options::new().log_errors(false);
+    assert!(matches!(Regex::with_policy("(a", &opts, &policy), Err(Error::Compile(_))));
+}
@@ -0,0 +1,56 @@
+use re2_rs_wrapper::{Prefilter, Regex};
+
+fn atom(s: &str) -> Prefilter {
+    Prefilter::Atom(s.to_string())
+}
+
+#[test]
+fn prefilter_single_literal() {
+    let re = Regex::new("hello").unwrap();
+    assert_eq!(re.prefilter(3), atom("hello"));
+}
+
+#[test]
+fn prefilter_and_of_literals() {
+    let re = Regex::new(r"hello\s+world").unwrap();
+    assert_eq!(re.prefilter(3), Prefilter::And(vec![atom("hello"), atom("world")]));
+}
+
+#[test]
+fn prefilter_or_of_literals_lowercased() {
+    let re = Regex::new(r"(?i)Apple|banana").unwrap();
+    let pf = re.prefilter(3);
+    let mut atoms = pf.atoms();
+    atoms.sort();
+    assert_eq!(atoms, vec!["apple", "banana"]);
+    assert!(matches!(pf, Prefilter::Or(_)));
+}
+
+#[test]
+fn prefilter_without_literals_is_all() {
+    let re = Regex::new(r"\d+").unwrap();
+    assert_eq!(re.prefilter(3), Prefilter::All);
+}
+
+#[test]
+fn prefilter_min_atom_len_prunes() {
+    let re = Regex::new(r"ab.*longer").unwrap();
+    assert_eq!(re.prefilter(1), Prefilter::And(vec![atom("ab"), atom("longer")]));
+    assert_eq!(re.prefilter(3), atom("longer"));
+    assert_eq!(re.prefilter(10), Prefilter::All);
+
+    // One short branch makes the whole alternation unfilterable
+    let re = Regex::new(r"ab|longer").unwrap();
+    assert_eq!(re.prefilter(3), Prefilter::All);
+}
+
+#[test]
+fn prefilter_prune_simplifies() {
+    let tree = Prefilter::Or(vec![
+        Prefilter::None,
+        Prefilter::And(vec![atom("x"), atom("needle")]),
+    ]);
+    assert_eq!(tree.clone().prune(0), Prefilter::And(vec![atom("x"), atom("needle")]));
+    assert_eq!(tree.prune(2), atom("needle"));
+    assert_eq!(Prefilter::And(vec![Prefilter::None, atom("a")]).prune(0), Prefilter::None);
+}
@@ -0,0 +1,54 @@
+use re2_rs_wrapper::{Anchor, Error, Options, RegexSet};
+
+#[test]
+fn set_matches_multiple_patterns() {
+    let mut set = RegexSet::new([r"foo\d+", r"bar", r"^baz$"], &Options::new(), Anchor::Unanchored).unwrap();
+    set.compile().unwrap();
+
+    assert_eq!(set.len(), 3);
+    assert_eq!(set.matches("foo42 and bar").unwrap(), vec![0, 1]);
+    assert_eq!(set.matches("baz").unwrap(), vec![2]);
+    assert!(set.matches("nothing here").unwrap().is_empty());
+    assert!(set.is_match("xbarx").unwrap());
+    assert!(!set.is_match("qux").unwrap());
+}
+
+#[test]
+fn set_add_returns_sequential_indices() {
+    let mut set = RegexSet::new(Vec::<&str>::new(), &Options::new(), Anchor::Unanchored).unwrap();
+    assert_eq!(set.add("a").unwrap(), 0);
+    assert_eq!(set.add("b").unwrap(), 1);
+    assert!(matches!(set.add(r"(?<=x)y"), Err(Error::Compile(_))));
+    assert_eq!(set.add("c").unwrap(), 2);
+    set.compile().unwrap();
+    assert_eq!(set.matches("cab").unwrap(), vec![0, 1, 2]);
+}
+
+#[test]
+fn set_anchor_both_requires_full_match() {
+    let mut set = RegexSet::new(["abc", r"\d+"], &Options::new(), Anchor::Both).unwrap();
+    set.compile().unwrap();
+    assert_eq!(set.matches("abc").unwrap(), vec![0]);
+    assert_eq!(set.matches("123").unwrap(), vec![1]);
+    assert!(set.matches("abc123").unwrap().is_empty());
+}
+
+#[test]
+fn set_respects_options() {
+    let opts = Options::new().case_insensitive(true);
+    let mut set = RegexSet::new(["hello", "world"], &opts, Anchor::Unanchored).unwrap();
+    set.compile().unwrap();
+    assert_eq!(set.matches("HELLO World").unwrap(), vec![0, 1]);
+}
+
+#[test]
+fn set_lifecycle_errors() {
+    let mut set = RegexSet::new(["a"], &Options::new(), Anchor::Unanchored).unwrap();
+    assert_eq!(set.matches("a"), Err(Error::NotCompiled));
+    assert_eq!(set.is_match("a"), Err(Error::NotCompiled));
+
+    set.compile().unwrap();
+    assert_eq!(set.add("b"), Err(Error::AlreadyCompiled));
+    assert_eq!(set.compile(), Err(Error::AlreadyCompiled));
+    assert_eq!(set.matches("a"), Ok(vec![0]));
+}
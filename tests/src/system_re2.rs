@@ -0,0 +1,37 @@
+use re2_rs_wrapper::{ast, debug, pcre_compatible, CompilePolicy, Error, Options, Prefilter, Regex};
+
+fn is_internal<T>(result: Result<T, Error>) -> bool {
+    matches!(result, Err(Error::Internal(msg)) if msg.contains("vendored RE2"))
+}
+
+#[test]
+fn system_re2_matching_is_unaffected() {
+    let re = Regex::new(r"(\w+)@(\w+)\.com").unwrap();
+    assert!(re.partial_match("mail bob@example.com"));
+    assert!(re.approx_heap_bytes() > 0);
+}
+
+#[test]
+fn system_re2_parse_tree_apis_fail() {
+    assert!(is_internal(ast::parse("a+", ast::ParseFlags::LIKE_PERL)));
+    assert!(is_internal(ast::canonicalize("a+", ast::ParseFlags::LIKE_PERL)));
+    assert!(is_internal(ast::simplify("a{2}", ast::ParseFlags::LIKE_PERL)));
+    assert!(!pcre_compatible("abc"));
+    assert!(is_internal(Regex::with_policy("a{2}", &Options::new(), &CompilePolicy::new().max_repeat(5))));
+}
+
+#[test]
+fn system_re2_introspection_falls_back() {
+    let re = Regex::new("hello world").unwrap();
+    assert_eq!(re.required_prefix(), None);
+    assert_eq!(re.prefilter(3), Prefilter::All);
+    assert_eq!(debug::dump(&re), "");
+    assert_eq!(debug::to_dot(&re), "");
+}
+
+#[test]
+fn system_re2_unicode_perl_classes_without_ranges() {
+    let opts = Options::new().unicode_perl_classes(true);
+    assert!(Regex::with_options(r"^\d\w+$", &opts).unwrap().full_match("٣κόσμος"));
+    assert!(is_internal(Regex::with_options(r"[\W]", &opts)));
+}
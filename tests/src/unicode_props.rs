@@ -1,5 +1,5 @@
 use re2_rs_wrapper::Regex as BasicRegex;
-use re2_rs_wrapper::Regex as IcuRegex;
+use re2_rs_icu::Regex as IcuRegex;
 // These tests are [FIXME SUPPOSED TO BE /FIXME]meant as documentation: how to use Unicode properties with RE2+ICU.
 // Run them against both `re2-rs` (ASCII/limited Unicode) and `re2-rs-icu` (full ICU).
 //
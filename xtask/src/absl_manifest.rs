@@ -0,0 +1,131 @@
+//! Generates re2-rs-sys/absl-sources.txt, the Abseil sources the build compiles.
+//!
+//! Starting from RE2 and the shim, follow `absl/...` includes to every header they reach.
+//! A reached header brings in the `.cc` of the same name, and a `.cc` without a header of
+//! its own (like `absl/time/duration.cc`, which implements `time.h`) comes in once it
+//! includes a reached header. New sources are scanned in turn until nothing changes.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MANIFEST: &str = "re2-rs-sys/absl-sources.txt";
+
+pub fn generate(root: &Path) {
+    let absl_root = root.join("vendor/abseil-cpp");
+
+    let mut roots = Vec::new();
+    for dir in ["vendor/re2", "vendor/re2/re2", "vendor/re2/util", "re2-rs-sys/src"] {
+        for entry in fs::read_dir(root.join(dir)).unwrap() {
+            let path = entry.unwrap().path();
+            if matches!(path.extension().and_then(|e| e.to_str()), Some("cc" | "h")) {
+                roots.push(path);
+            }
+        }
+    }
+
+    let orphans: Vec<String> = all_sources(&absl_root)
+        .into_iter()
+        .filter(|cc| !absl_root.join(cc.replace(".cc", ".h")).exists())
+        .collect();
+
+    let mut headers = BTreeSet::new();
+    let mut sources = BTreeSet::new();
+    let mut queue: VecDeque<PathBuf> = roots.into();
+    loop {
+        while let Some(file) = queue.pop_front() {
+            for header in absl_includes(&file) {
+                if !absl_root.join(&header).exists() || !headers.insert(header.clone()) {
+                    continue;
+                }
+                queue.push_back(absl_root.join(&header));
+                let Some(stem) = header.strip_suffix(".h") else { continue };
+                let cc = format!("{}.cc", stem);
+                if !is_excluded(&cc) && absl_root.join(&cc).exists() && sources.insert(cc.clone()) {
+                    queue.push_back(absl_root.join(cc));
+                }
+            }
+        }
+        for cc in &orphans {
+            if !sources.contains(cc)
+                && absl_includes(&absl_root.join(cc)).iter().any(|h| headers.contains(h))
+            {
+                sources.insert(cc.clone());
+                queue.push_back(absl_root.join(cc));
+            }
+        }
+        if queue.is_empty() {
+            break;
+        }
+    }
+
+    let mut out = String::from(
+        "# Abseil sources compiled by re2-rs-sys, relative to vendor/abseil-cpp.\n\
+         # Generated by `cargo run -p xtask -- absl-manifest`; do not edit by hand.\n",
+    );
+    for cc in &sources {
+        out.push_str(cc);
+        out.push('\n');
+    }
+    let dest = root.join(MANIFEST);
+    fs::write(&dest, out).expect("Failed to write the Abseil manifest");
+    println!("{} sources ({} headers reached) written to {}", sources.len(), headers.len(), dest.display());
+}
+
+/// `absl/...` paths included by `file`. Besides `#include "..."` and `#include <...>`, any
+/// quoted header path counts, which catches `#include MACRO` as used for the stacktrace
+/// implementations (`ABSL_STACKTRACE_INL_HEADER`).
+fn absl_includes(file: &Path) -> Vec<String> {
+    let text = fs::read_to_string(file).unwrap_or_default();
+    let mut found = Vec::new();
+    for line in text.lines().map(str::trim_start) {
+        if line.starts_with("//") || line.starts_with('*') {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("#include") {
+            let rest = rest.trim();
+            if let Some(inner) = rest.strip_prefix('<').and_then(|r| r.split('>').next())
+                && inner.starts_with("absl/")
+            {
+                found.push(inner.to_string());
+            }
+        }
+        for (i, quoted) in line.split('"').enumerate() {
+            if i % 2 == 1
+                && quoted.starts_with("absl/")
+                && (quoted.ends_with(".h") || quoted.ends_with(".inc"))
+            {
+                found.push(quoted.to_string());
+            }
+        }
+    }
+    found
+}
+
+/// Every non-test `.cc` under `absl/`, relative to the Abseil root
+fn all_sources(absl_root: &Path) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut dirs = vec![absl_root.join("absl")];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let rel = path.strip_prefix(absl_root).unwrap().to_string_lossy().replace('\\', "/");
+            if rel.ends_with(".cc") && !is_excluded(&rel) {
+                found.push(rel);
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+fn is_excluded(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    ["test", "benchmark", "mock", "gentables", "print_hash_of"]
+        .iter()
+        .any(|word| name.contains(word))
+}
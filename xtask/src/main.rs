@@ -1,7 +1,20 @@
-use std::{env, fs, path::PathBuf, process::Command};
+use std::{env, fs, path::{Path, PathBuf}, process::Command};
+
+mod absl_manifest;
 
 fn main() {
     let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf();
+    match env::args().nth(1).as_deref() {
+        None | Some("regen-bindings") => regen_bindings(&root),
+        Some("absl-manifest") => absl_manifest::generate(&root),
+        Some(other) => {
+            eprintln!("unknown task `{}`; expected `regen-bindings` or `absl-manifest`", other);
+            std::process::exit(2);
+        }
+    }
+}
+
+fn regen_bindings(root: &Path) {
     println!("Regenerating bindings with bindgen…");
     let sys_crate = root.join("re2-rs-sys");
 
@@ -11,7 +24,7 @@ fn main() {
         .arg("re2-rs-sys")
         .arg("--features")
         .arg("bindgen")
-        .current_dir(&root)
+        .current_dir(root)
         .status()
         .expect("failed to run cargo build with bindgen");
 